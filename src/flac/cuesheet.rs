@@ -0,0 +1,196 @@
+use crate::wav::CuePoint;
+
+use super::metadata::block_header;
+
+/// Represents a CUESHEET metadata block
+/// 
+/// A cue sheet describes the track layout of the audio, most commonly
+/// of a CD image. Every cue sheet must end with a lead-out track, which
+/// has the track number 170 for CDs and 255 otherwise.
+pub struct CueSheet {
+    pub media_catalog_number: String,
+    pub lead_in_samples: u64,
+    pub is_cd: bool,
+    pub tracks: Vec <CueSheetTrack>,
+}
+
+/// Represents a track of a CUESHEET metadata block
+/// 
+/// The `offset` is in samples relative to the start of the audio, and
+/// the offsets of the index points are relative to the track offset.
+pub struct CueSheetTrack {
+    pub offset: u64,
+    pub number: u8,
+    pub isrc: String,
+    pub is_audio: bool,
+    pub pre_emphasis: bool,
+    pub index_points: Vec <CueSheetIndex>,
+}
+
+/// Represents an index point of a CUESHEET track
+pub struct CueSheetIndex {
+    pub offset: u64,
+    pub number: u8,
+}
+
+impl CueSheet {
+    /// Build a cue sheet from the cue points of a WAV file
+    /// 
+    /// Each cue point becomes an audio track with a single index point
+    /// at the start of the track, numbered in the order of the cue points.
+    /// A lead-out track is added at `total_samples`.
+    pub fn from_cue_points(cue_points: &[CuePoint], total_samples: u64) -> Self {
+
+        let mut tracks: Vec <CueSheetTrack> = Vec::new();
+
+        for (i, each_cue) in cue_points.iter().enumerate() {
+            tracks.push(CueSheetTrack {
+                offset: u64::from(each_cue.sample_offset),
+                number: (i + 1) as u8,
+                isrc: String::new(),
+                is_audio: true,
+                pre_emphasis: false,
+                index_points: vec![CueSheetIndex { offset: 0, number: 1 }],
+            });
+        }
+
+        tracks.push(CueSheetTrack {
+            offset: total_samples,
+            number: 255,
+            isrc: String::new(),
+            is_audio: true,
+            pre_emphasis: false,
+            index_points: Vec::new(),
+        });
+
+        CueSheet {
+            media_catalog_number: String::new(),
+            lead_in_samples: 0,
+            is_cd: false,
+            tracks,
+        }
+
+    }
+
+    /// Serialize the cue sheet into a CUESHEET metadata block
+    /// 
+    /// The output includes the 4-byte metadata block header, with the
+    /// last-metadata-block flag set if `is_last` is true. The catalog
+    /// number and ISRCs are zero-padded and truncated if too long.
+    /// 
+    /// # Errors
+    /// `None` is returned if the cue sheet does not fit in the 24-bit
+    /// length of the metadata block header.
+    pub fn write(&self, is_last: bool) -> Option <Vec <u8>> {
+
+        let mut body: Vec <u8> = Vec::new();
+
+        write_padded_str(&mut body, &self.media_catalog_number, 128);
+        body.extend_from_slice(&self.lead_in_samples.to_be_bytes());
+        body.push(if self.is_cd { 0x80 } else { 0x00 }); // 1-bit flag, 7 reserved bits
+        body.extend_from_slice(&[0; 258]);
+        body.push(self.tracks.len() as u8);
+
+        for each_track in &self.tracks {
+
+            body.extend_from_slice(&each_track.offset.to_be_bytes());
+            body.push(each_track.number);
+            write_padded_str(&mut body, &each_track.isrc, 12);
+
+            let mut flags: u8 = 0; // 2-bit flags, 6 reserved bits
+            if !each_track.is_audio {
+                flags |= 0x80;
+            }
+            if each_track.pre_emphasis {
+                flags |= 0x40;
+            }
+            body.push(flags);
+            body.extend_from_slice(&[0; 13]);
+            body.push(each_track.index_points.len() as u8);
+
+            for each_index in &each_track.index_points {
+                body.extend_from_slice(&each_index.offset.to_be_bytes());
+                body.push(each_index.number);
+                body.extend_from_slice(&[0; 3]);
+            }
+
+        }
+
+        let mut block = block_header(5, is_last, body.len())?; // CUESHEET is block type 5
+        block.append(&mut body);
+
+        Some(block)
+
+    }
+
+}
+
+/// Append `text` as a zero-padded field of exactly `len` bytes
+fn write_padded_str(out: &mut Vec <u8>, text: &str, len: usize) {
+
+    let bytes = text.as_bytes();
+    let used = bytes.len().min(len);
+
+    out.extend_from_slice(&bytes[..used]);
+    out.resize(out.len() + len - used, 0);
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Offsets into a serialized block, including the 4-byte header
+    const NUM_TRACKS_POS: usize = 4 + 128 + 8 + 259;
+    const FIRST_TRACK_POS: usize = NUM_TRACKS_POS + 1;
+    const TRACK_LEN: usize = 36;
+    const INDEX_LEN: usize = 12;
+
+    #[test]
+    fn write_tracks() {
+        let cue_points = [
+            CuePoint { id: 1, sample_offset: 0 },
+            CuePoint { id: 2, sample_offset: 44100 },
+        ];
+        let block = CueSheet::from_cue_points(&cue_points, 88200).write(true).unwrap();
+
+        assert_eq!(block[0], 0x85);
+        assert_eq!(block.len(), FIRST_TRACK_POS + 3 * TRACK_LEN + 2 * INDEX_LEN);
+        assert_eq!(&block[1..4], &((block.len() - 4) as u32).to_be_bytes()[1..4]);
+        assert_eq!(block[NUM_TRACKS_POS], 3);
+
+        let second_track_pos = FIRST_TRACK_POS + TRACK_LEN + INDEX_LEN;
+        assert_eq!(&block[second_track_pos..second_track_pos + 8], &44100u64.to_be_bytes());
+        assert_eq!(block[second_track_pos + 8], 2);
+
+        let lead_out_pos = second_track_pos + TRACK_LEN + INDEX_LEN;
+        assert_eq!(&block[lead_out_pos..lead_out_pos + 8], &88200u64.to_be_bytes());
+        assert_eq!(block[lead_out_pos + 8], 255);
+        assert_eq!(block[lead_out_pos + TRACK_LEN - 1], 0);
+    }
+
+    #[test]
+    fn write_catalog_and_isrc() {
+        let cue_sheet = CueSheet {
+            media_catalog_number: String::from("1234567890123"),
+            lead_in_samples: 88200,
+            is_cd: true,
+            tracks: vec![CueSheetTrack {
+                offset: 0,
+                number: 170,
+                isrc: String::from("USABC1234567"),
+                is_audio: false,
+                pre_emphasis: true,
+                index_points: Vec::new(),
+            }],
+        };
+        let block = cue_sheet.write(false).unwrap();
+
+        assert_eq!(block[0], 0x05);
+        assert_eq!(&block[4..17], b"1234567890123");
+        assert!(block[17..132].iter().all(|byte| *byte == 0));
+        assert_eq!(block[140], 0x80);
+        assert_eq!(&block[FIRST_TRACK_POS + 9..FIRST_TRACK_POS + 21], b"USABC1234567");
+        assert_eq!(block[FIRST_TRACK_POS + 21], 0xC0);
+    }
+}
@@ -17,7 +17,7 @@ impl <T> CrcOptions <T> {
     /// Create a builder to the CRC encoder
     pub fn new(poly: T, poly_len: T) -> Self {
         
-        CrcOptions {poly: poly, poly_len: poly_len,}
+        CrcOptions {poly, poly_len,}
 
     }
 
@@ -27,57 +27,86 @@ impl CrcOptions <u8> {
     /// Encode data using CRC8 encoding
     /// 
     /// This method is available only if `CrcOptions` is of type `u8`.
-    pub fn build_crc8(&self, data: &Vec <u8>) -> u8 {
+    /// 
+    /// # Errors
+    /// `None` is returned if `poly_len` is not within 1 and 8 inclusive,
+    /// if `poly` does not fit in `poly_len` bits, or if `data` contains
+    /// values other than 0 and 1.
+    pub fn build_crc8(&self, data: &[u8]) -> Option <u8> {
+
+        if self.poly_len == 0 || self.poly_len > 8 {
+            return None;
+        }
+
+        if self.poly.checked_shr(self.poly_len as u32).unwrap_or(0) != 0 || data.iter().any(|bit| *bit > 1) {
+            return None;
+        }
 
         let mut div_orig: Vec<u8> = Vec::new(); // contains the dividend
-        div_orig.append(&mut data.clone());
+        div_orig.extend_from_slice(data);
         div_orig.append(&mut vec![0; self.poly_len.into()]);
 
-        let mut div = self.bin_to_int(&div_orig[0..self.poly_len as usize].to_vec());
+        let mut div = self.bin_to_int(&div_orig[0..self.poly_len as usize]);
         let base_two: u8 = 2;
+        let msb = base_two.checked_pow((self.poly_len-1) as u32)?;
 
         for i in 0..data.len() {
 
-            if div >= base_two.pow((self.poly_len-1) as u32) { 
+            if div >= msb { 
 
                 // if MSB is 1, XOR div with poly
-                div = div - base_two.pow((self.poly_len-1) as u32);
-                div = div*2 + div_orig[self.poly_len as usize + i];
-                div = div^self.poly;
+                div = div.checked_sub(msb)?;
+                div = div.checked_mul(2)?.checked_add(div_orig[self.poly_len as usize + i])?;
+                div ^= self.poly;
                 
             } else {
 
-                // if MSB is 0, XOR div with 0
-                div = div*2 + div_orig[self.poly_len as usize + i];
-                div = div^0;
+                // if MSB is 0, XOR div with 0, which leaves it unchanged
+                div = div.checked_mul(2)?.checked_add(div_orig[self.poly_len as usize + i])?;
 
             }
 
         }
 
-        return div;
+        Some(div)
 
     }
 
-    pub fn combine_crc8(&self, mut data: Vec<u8>, mut checksum: u8) -> Vec<u8> {
+    /// Append the bits of `checksum` to `data`, MSB first
+    /// 
+    /// Only the low `poly_len` bits of `checksum` are appended. Building
+    /// the CRC of the result gives 0 if `checksum` is the CRC of `data`.
+    pub fn combine_crc8(&self, mut data: Vec<u8>, checksum: u8) -> Vec<u8> {
 
-        let base_two: u8 = 2;
-        let mut data_with_crc: Vec<u8> = Vec::new();
+        for i in (0..self.poly_len).rev() {
 
-        for i in 0..self.poly_len {
+            data.push((checksum >> i) & 1);
 
-            let bit = checksum/base_two.pow((self.poly_len-1) as u32);
-            checksum = checksum<<1;
+        }
 
-            data.push(bit);
+        data
 
-        }
+    }
+
+    /// Check that appending the CRC of `data` gives a CRC of 0
+    /// 
+    /// This builds the CRC of `data`, appends it with `combine_crc8()`
+    /// and builds the CRC again. `false` is returned if the second CRC is
+    /// not 0 or if either CRC cannot be built.
+    pub fn self_check(&self, data: &[u8]) -> bool {
 
-        return data
+        let checksum = match self.build_crc8(data) {
+            Some(checksum) => checksum,
+            None => return false,
+        };
+
+        let data_with_crc = self.combine_crc8(data.to_vec(), checksum);
+
+        self.build_crc8(&data_with_crc) == Some(0)
 
     }
 
-    fn bin_to_int(&self, bin_fmt: &Vec <u8>) -> u8 {
+    fn bin_to_int(&self, bin_fmt: &[u8]) -> u8 {
 
         let mut int_fmt: u8 = 0;
         let base_two: u8 = 2;
@@ -85,12 +114,12 @@ impl CrcOptions <u8> {
         for i in 0..self.poly_len {
 
             if bin_fmt[i as usize] == 1 {
-                int_fmt = int_fmt + base_two.pow((self.poly_len-1-i) as u32);
+                int_fmt += base_two.pow((self.poly_len-1-i) as u32);
             }
         
         }
 
-        return int_fmt;
+        int_fmt
 
     }
 
@@ -100,57 +129,86 @@ impl CrcOptions <u16> {
     /// Encode data using CRC16 encoding
     /// 
     /// This method is available only if `CrcOptions` is of type `u16`.
-    pub fn build_crc16(&self, data: &Vec <u16>) -> u16 {
+    /// 
+    /// # Errors
+    /// `None` is returned if `poly_len` is not within 1 and 16 inclusive,
+    /// if `poly` does not fit in `poly_len` bits, or if `data` contains
+    /// values other than 0 and 1.
+    pub fn build_crc16(&self, data: &[u16]) -> Option <u16> {
+
+        if self.poly_len == 0 || self.poly_len > 16 {
+            return None;
+        }
+
+        if self.poly.checked_shr(self.poly_len as u32).unwrap_or(0) != 0 || data.iter().any(|bit| *bit > 1) {
+            return None;
+        }
 
         let mut div_orig: Vec<u16> = Vec::new(); // contains the dividend
-        div_orig.append(&mut data.clone());
+        div_orig.extend_from_slice(data);
         div_orig.append(&mut vec![0; self.poly_len.into()]);
 
-        let mut div = self.bin_to_int(&div_orig[0..self.poly_len as usize].to_vec());
+        let mut div = self.bin_to_int(&div_orig[0..self.poly_len as usize]);
         let base_two: u16 = 2;
+        let msb = base_two.checked_pow((self.poly_len-1) as u32)?;
 
         for i in 0..data.len() {
 
-            if div >= base_two.pow((self.poly_len-1) as u32) { 
+            if div >= msb { 
 
                 // if MSB is 1, XOR div with poly
-                div = div - base_two.pow((self.poly_len-1) as u32);
-                div = div*2 + div_orig[self.poly_len as usize + i];
-                div = div^self.poly;
+                div = div.checked_sub(msb)?;
+                div = div.checked_mul(2)?.checked_add(div_orig[self.poly_len as usize + i])?;
+                div ^= self.poly;
                 
             } else {
 
-                // if MSB is 0, XOR div with 0
-                div = div*2 + div_orig[self.poly_len as usize + i];
-                div = div^0;
+                // if MSB is 0, XOR div with 0, which leaves it unchanged
+                div = div.checked_mul(2)?.checked_add(div_orig[self.poly_len as usize + i])?;
 
             }
 
         }
 
-        return div;
+        Some(div)
 
     }
 
-    pub fn combine_crc16(&self, mut data: Vec<u16>, mut checksum: u16) -> Vec<u16> {
+    /// Append the bits of `checksum` to `data`, MSB first
+    /// 
+    /// Only the low `poly_len` bits of `checksum` are appended. Building
+    /// the CRC of the result gives 0 if `checksum` is the CRC of `data`.
+    pub fn combine_crc16(&self, mut data: Vec<u16>, checksum: u16) -> Vec<u16> {
 
-        let base_two: u16 = 2;
-        let mut data_with_crc: Vec<u16> = Vec::new();
+        for i in (0..self.poly_len).rev() {
 
-        for i in 0..self.poly_len {
+            data.push((checksum >> i) & 1);
+
+        }
 
-            let bit = checksum/base_two.pow((self.poly_len-1) as u32);
-            checksum = checksum<<1;
+        data
 
-            data.push(bit);
+    }
 
-        }
+    /// Check that appending the CRC of `data` gives a CRC of 0
+    /// 
+    /// This builds the CRC of `data`, appends it with `combine_crc16()`
+    /// and builds the CRC again. `false` is returned if the second CRC is
+    /// not 0 or if either CRC cannot be built.
+    pub fn self_check(&self, data: &[u16]) -> bool {
 
-        return data
+        let checksum = match self.build_crc16(data) {
+            Some(checksum) => checksum,
+            None => return false,
+        };
+
+        let data_with_crc = self.combine_crc16(data.to_vec(), checksum);
+
+        self.build_crc16(&data_with_crc) == Some(0)
 
     }
 
-    fn bin_to_int(&self, bin_fmt: &Vec <u16>) -> u16 {
+    fn bin_to_int(&self, bin_fmt: &[u16]) -> u16 {
 
         let mut int_fmt: u16 = 0;
         let base_two: u16 = 2;
@@ -158,12 +216,12 @@ impl CrcOptions <u16> {
         for i in 0..self.poly_len {
 
             if bin_fmt[i as usize] == 1 {
-                int_fmt = int_fmt + base_two.pow((self.poly_len-1-i) as u32);
+                int_fmt += base_two.pow((self.poly_len-1-i) as u32);
             }
         
         }
 
-        return int_fmt;
+        int_fmt
 
     }
 
@@ -181,7 +239,7 @@ pub fn crc8_encode() {
 
     let builder_crc8 = CrcOptions::new(poly_crc8, poly_len_crc8);
 
-    let checksum_crc8 = builder_crc8.build_crc8(&data_crc8);
+    let checksum_crc8 = builder_crc8.build_crc8(&data_crc8).unwrap();
     println!("{}", checksum_crc8);
 
     let data_with_crc8 = builder_crc8.combine_crc8(data_crc8.clone(), checksum_crc8);
@@ -200,7 +258,7 @@ pub fn crc8_encode() {
 
     let builder_crc16 = CrcOptions::new(poly_crc16, poly_len_crc16);
 
-    let checksum_crc16 = builder_crc16.build_crc16(&data_crc16);
+    let checksum_crc16 = builder_crc16.build_crc16(&data_crc16).unwrap();
     println!("{}", checksum_crc16);
 
     let data_with_crc16 = builder_crc16.combine_crc16(data_crc16.clone(), checksum_crc16);
@@ -209,4 +267,84 @@ pub fn crc8_encode() {
     let check_crc16 = builder_crc16.build_crc16(&data_with_crc16);
     println!("{:?}", check_crc16);
 
-}   
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc8_known_value() {
+        let builder = CrcOptions::new(0b0000_0111u8, 8u8);
+
+        assert_eq!(builder.build_crc8(&[0, 0, 0, 0, 0, 0, 0, 1]), Some(0x07));
+    }
+
+    #[test]
+    fn crc16_known_value() {
+        let builder = CrcOptions::new(0x8005u16, 16u16);
+
+        assert_eq!(builder.build_crc16(&[0, 0, 0, 0, 0, 0, 0, 1]), Some(0x8005));
+    }
+
+    #[test]
+    fn crc_smallest_poly() {
+        // x^1 + 1 is the parity bit
+        let builder = CrcOptions::new(0b1u8, 1u8);
+
+        assert_eq!(builder.build_crc8(&[1, 0, 1, 1]), Some(1));
+        assert_eq!(builder.build_crc8(&[1, 0, 1, 0]), Some(0));
+    }
+
+    #[test]
+    fn crc_bad_options() {
+        assert_eq!(CrcOptions::new(0b1u8, 0u8).build_crc8(&[1, 0]), None);
+        assert_eq!(CrcOptions::new(0b1u8, 9u8).build_crc8(&[1, 0]), None);
+        assert_eq!(CrcOptions::new(0b100u8, 2u8).build_crc8(&[1, 0]), None);
+        assert_eq!(CrcOptions::new(0b1u16, 17u16).build_crc16(&[1, 0]), None);
+        assert_eq!(CrcOptions::new(0b0000_0111u8, 8u8).build_crc8(&[2]), None);
+    }
+
+    fn random_bits(seed: u32, len: usize) -> Vec <u8> {
+        // Linear congruential generator for reproducible inputs
+        let mut state = seed;
+        (0..len).map(|_| {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 31) as u8
+        }).collect()
+    }
+
+    #[test]
+    fn combine_short_poly() {
+        // x^3 + x + 1
+        let builder = CrcOptions::new(0b011u8, 3u8);
+
+        assert_eq!(builder.combine_crc8(vec![1, 1], 0b101), vec![1, 1, 1, 0, 1]);
+        assert!(builder.self_check(&[1, 1, 0, 1, 0, 0, 1]));
+    }
+
+    #[test]
+    fn self_check_flac_crc8() {
+        let builder = CrcOptions::new(0b0000_0111u8, 8u8);
+
+        for seed in 0..16 {
+            assert!(builder.self_check(&random_bits(seed, 8 + seed as usize * 13)));
+        }
+    }
+
+    #[test]
+    fn self_check_flac_crc16() {
+        let builder = CrcOptions::new(0x8005u16, 16u16);
+
+        for seed in 0..16 {
+            let bits: Vec <u16> = random_bits(seed, 16 + seed as usize * 29).iter().map(|bit| u16::from(*bit)).collect();
+            assert!(builder.self_check(&bits));
+        }
+    }
+
+    #[test]
+    fn self_check_bad_options() {
+        assert!(!CrcOptions::new(0b1u8, 0u8).self_check(&[1, 0, 1]));
+        assert!(!CrcOptions::new(0b0000_0111u8, 8u8).self_check(&[2]));
+    }
+}
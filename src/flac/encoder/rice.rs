@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+
+use super::utf8::int_to_bits_msb;
+
+pub struct RiceEncoder;
+
+impl RiceEncoder {
+
+    pub fn encode(num: u64) -> Vec<u8> {
+
+        let param = 16;
+        let k = 4;
+        let mut rice_encoding: Vec<u8> = Vec::new();
+
+        let unary = num >> k;
+        let bin = num & (param - 1);
+
+        rice_encoding.resize(unary as usize, 1);
+        
+        rice_encoding.push(0);
+
+        rice_encoding.append(&mut int_to_bits_msb(bin, k));
+
+        rice_encoding
+
+    }
+
+    /// Get the number of bits the Rice encoding of `residuals` would take
+    /// 
+    /// Each residual is zigzag-mapped to an unsigned number and takes
+    /// `(num >> k) + 1 + k` bits: the unary quotient, its stop bit and the
+    /// `k`-bit remainder. No encoding is actually produced, which makes this
+    /// suitable for searching for the best Rice parameter.
+    /// 
+    /// # Errors
+    /// `None` is returned if `k` is above 30, the largest Rice parameter
+    /// FLAC can store in its 5-bit parameter field.
+    pub fn encoded_len(residuals: &[i64], k: u8) -> Option <usize> {
+
+        if k > 30 {
+            return None;
+        }
+
+        let mut total_len: usize = 0;
+
+        for each_residual in residuals {
+            total_len += (zigzag(*each_residual) >> k) as usize + 1 + k as usize;
+        }
+
+        Some(total_len)
+
+    }
+
+    /// Split the residuals of a subframe into its Rice partitions
+    /// 
+    /// There are `2^partition_order` partitions of `block_size >> partition_order`
+    /// samples each. The first `predictor_order` samples of the block are
+    /// warm-up samples rather than residuals, so the first partition holds
+    /// that many fewer residuals than the others.
+    /// 
+    /// # Errors
+    /// `None` is returned if the number of residuals is not
+    /// `block_size - predictor_order`, if `block_size` is not divisible by
+    /// the number of partitions, or if the first partition would be smaller
+    /// than the predictor order.
+    pub fn partition_residual(residuals: &[i64], block_size: usize, predictor_order: usize, partition_order: u8) -> Option <Vec <&[i64]>> {
+
+        let num_partitions = 1usize.checked_shl(partition_order as u32)?;
+        let partition_size = block_size >> partition_order;
+
+        if residuals.len() + predictor_order != block_size || partition_size * num_partitions != block_size {
+            return None;
+        }
+
+        if partition_size < predictor_order {
+            return None;
+        }
+
+        let mut partitions: Vec <&[i64]> = Vec::with_capacity(num_partitions);
+        let (first, mut rest) = residuals.split_at(partition_size - predictor_order);
+        partitions.push(first);
+
+        while !rest.is_empty() {
+            let (partition, remaining) = rest.split_at(partition_size);
+            partitions.push(partition);
+            rest = remaining;
+        }
+
+        Some(partitions)
+
+    }
+
+}
+
+/// Map a signed residual to an unsigned number for Rice encoding
+/// 
+/// Non-negative numbers are mapped to even numbers and negative numbers
+/// to odd numbers, i.e. 0, -1, 1, -2, 2 becomes 0, 1, 2, 3, 4.
+pub fn zigzag(num: i64) -> u64 {
+
+    ((num << 1) ^ (num >> 63)) as u64
+
+}
+
+/// Find a lower partition order that keeps the same Rice parameters
+/// 
+/// `params` holds the Rice parameter of each partition, so its length is
+/// `2^partition_order`. If every group of adjacent partitions that would
+/// merge at a lower order shares one parameter, the residual encodes to
+/// the same bits at that order with fewer parameters to store. The lowest
+/// such order is returned, which is 0 if all partitions share a parameter.
+/// 
+/// # Errors
+/// `None` is returned if no lower order is possible or if the length of
+/// `params` is not a power of two.
+pub fn coalesce_partitions(params: &[u8]) -> Option <u8> {
+
+    if !params.len().is_power_of_two() {
+        return None;
+    }
+
+    let partition_order = params.len().trailing_zeros() as u8;
+
+    for order in 0..partition_order {
+        let group_len = params.len() >> order;
+        if params.chunks(group_len).all(|group| group.iter().all(|k| *k == group[0])) {
+            return Some(order);
+        }
+    }
+
+    None
+
+}
+
+/// Estimate the first-order entropy of a residual block
+/// 
+/// The entropy is in bits per sample and is computed from how often each
+/// residual value occurs in `residuals`. It is a lower bound for any coder
+/// that encodes each residual on its own, so comparing it to the Rice
+/// coded size shows how much room for improvement is left. An empty or
+/// constant block has an entropy of 0.0.
+pub fn residual_entropy_bits(residuals: &[i64]) -> f64 {
+
+    let mut counts: HashMap<i64, usize> = HashMap::new();
+    for residual in residuals {
+        *counts.entry(*residual).or_insert(0) += 1;
+    }
+
+    let total = residuals.len() as f64;
+    let mut entropy = 0.0;
+    for count in counts.values() {
+        let p = *count as f64 / total;
+        entropy -= p * p.log2();
+    }
+
+    entropy
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zigzag_order() {
+        let nums: Vec <u64> = [0, -1, 1, -2, 2].iter().map(|num| zigzag(*num)).collect();
+
+        assert_eq!(nums, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn encoded_len_matches_encode() {
+        let residuals: Vec <i64> = vec![0, 3, -3, 17, -42, 100, -1, 255];
+
+        let actual_len: usize = residuals.iter()
+            .map(|residual| RiceEncoder::encode(zigzag(*residual)).len())
+            .sum();
+
+        assert_eq!(RiceEncoder::encoded_len(&residuals, 4), Some(actual_len));
+    }
+
+    #[test]
+    fn encoded_len_bad_param() {
+        assert_eq!(RiceEncoder::encoded_len(&[1, -1], 30), Some(2 * 31));
+        assert_eq!(RiceEncoder::encoded_len(&[1, -1], 31), None);
+        assert_eq!(RiceEncoder::encoded_len(&[1, -1], 64), None);
+    }
+
+    #[test]
+    fn partition_first_excludes_warmup() {
+        let residuals = vec![0i64; 4096 - 8];
+        let partitions = RiceEncoder::partition_residual(&residuals, 4096, 8, 2).unwrap();
+
+        let sizes: Vec <usize> = partitions.iter().map(|partition| partition.len()).collect();
+        assert_eq!(sizes, vec![1024 - 8, 1024, 1024, 1024]);
+    }
+
+    #[test]
+    fn partition_order_0() {
+        let residuals: Vec <i64> = (0..12).collect();
+        let partitions = RiceEncoder::partition_residual(&residuals, 16, 4, 0).unwrap();
+
+        assert_eq!(partitions, vec![&residuals[..]]);
+    }
+
+    #[test]
+    fn coalesce_uniform_params() {
+        assert_eq!(coalesce_partitions(&[5; 16]), Some(0));
+        assert_eq!(coalesce_partitions(&[3, 3, 5, 5]), Some(1));
+        assert_eq!(coalesce_partitions(&[3, 3, 3, 3, 5, 5, 4, 4]), Some(2));
+    }
+
+    #[test]
+    fn coalesce_not_possible() {
+        assert_eq!(coalesce_partitions(&[3, 4]), None);
+        assert_eq!(coalesce_partitions(&[7]), None);
+        assert_eq!(coalesce_partitions(&[7, 7, 7]), None);
+        assert_eq!(coalesce_partitions(&[]), None);
+    }
+
+    #[test]
+    fn entropy_constant() {
+        assert_eq!(residual_entropy_bits(&[5; 64]), 0.0);
+        assert_eq!(residual_entropy_bits(&[]), 0.0);
+    }
+
+    #[test]
+    fn entropy_uniform() {
+        // Linear congruential generator for reproducible 8-bit residuals
+        let mut state: u32 = 1;
+        let residuals: Vec<i64> = (0..65536).map(|_| {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 24) as i64 - 128
+        }).collect();
+
+        let entropy = residual_entropy_bits(&residuals);
+        assert!(entropy > 7.9 && entropy <= 8.0);
+    }
+
+    #[test]
+    fn partition_bad_counts() {
+        assert!(RiceEncoder::partition_residual(&[0; 4096], 4096, 8, 2).is_none());
+        assert!(RiceEncoder::partition_residual(&[0; 4088], 4096, 8, 10).is_none());
+        assert!(RiceEncoder::partition_residual(&[0; 4095], 4100, 5, 3).is_none());
+    }
+}
\ No newline at end of file
@@ -5,55 +5,49 @@ impl Utf8Encoder {
     /// 
     /// Although UTF-8 encoding is for characters, characters are
     /// mapped to certain numbers.
-    pub fn encode(mut num: u64) -> Vec<u8> {
+    pub fn encode(num: u64) -> Vec<u8> {
         
-        let num_vec: Vec<u8> = int_to_bin(num);
+        let num_len = int_to_bin(num).len();
         let mut bin_temp: Vec<u8> = Vec::new();
 
-        println!("{:?}", num_vec);
-
-        if num_vec.len() <= 7 {
-            bin_temp = vec![0,2,2,2,2,2,2];
-        } else if num_vec.len() <= 11 {
+        if num_len <= 7 {
+            bin_temp = vec![0,2,2,2,2,2,2,2];
+        } else if num_len <= 11 {
             bin_temp = vec![1,1,0,2,2,2,2,2,1,0,2,2,2,2,2,2];
-        } else if num_vec.len() <= 16 {
+        } else if num_len <= 16 {
             bin_temp = vec![1,1,1,0,2,2,2,2,1,0,2,2,2,2,2,2,1,0,2,2,2,2,2,2];
-        } else if num_vec.len() <= 21 {
+        } else if num_len <= 21 {
             bin_temp = vec![1,1,1,1,0,2,2,2,1,0,2,2,2,2,2,2,1,0,2,2,2,2,2,2,1,0,2,2,2,2,2,2];
-        } else if num_vec.len() <= 26 {
+        } else if num_len <= 26 {
             bin_temp = vec![1,1,1,1,1,0,2,2,1,0,2,2,2,2,2,2,1,0,2,2,2,2,2,2,1,0,2,2,2,2,2,2,1,0,2,2,2,2,2,2];
-        } else if num_vec.len() <= 31 {
+        } else if num_len <= 31 {
             bin_temp = vec![1,1,1,1,1,1,0,2,1,0,2,2,2,2,2,2,1,0,2,2,2,2,2,2,1,0,2,2,2,2,2,2,1,0,2,2,2,2,2,2,1,0,2,2,2,2,2,2];
-        } else if num_vec.len() <= 40 {
+        } else if num_len <= 40 {
             bin_temp = vec![1,1,1,1,1,1,1,0,1,0,2,2,2,2,2,2,1,0,2,2,2,2,2,2,1,0,2,2,2,2,2,2,1,0,2,2,2,2,2,2,1,0,2,2,2,2,2,2,1,0,2,2,2,2,2,2];
         }
 
-        let mut bit_sel = bin_temp.len()-1;
-
-        for i in 0..num_vec.len() {
-
-            while bin_temp[bit_sel] != 2 {
-                bit_sel -= 1;
-            }
-
-            bin_temp[bit_sel] = num_vec[i];
-
-        }
+        // Fill the free bits (marked with 2) with the number, MSB first
+        let num_slots = bin_temp.iter().filter(|bit| **bit == 2).count();
+        let mut num_bits = int_to_bits_msb(num, num_slots).into_iter();
 
-        for i in 0..bin_temp.len() {
+        for each_bit in bin_temp.iter_mut() {
 
-            if bin_temp[i] == 2 {
-                bin_temp[i] = 0;
+            if *each_bit == 2 {
+                *each_bit = num_bits.next().unwrap();
             }
 
         }
 
-        return bin_temp;
+        bin_temp
 
     }
     
 }
 
+/// Convert a number into its binary digits, LSB first
+/// 
+/// Only as many bits as needed are produced, so zero yields an empty
+/// vector. Use `int_to_bits_msb()` for bits in FLAC bitstream order.
 pub fn int_to_bin(mut int_fmt: u64) -> Vec<u8> {
 
     let mut bin_fmt: Vec<u8> = Vec::new();
@@ -61,10 +55,58 @@ pub fn int_to_bin(mut int_fmt: u64) -> Vec<u8> {
     while int_fmt > 0 {
 
         bin_fmt.push((int_fmt - int_fmt/2*2) as u8);
-        int_fmt = int_fmt/2;
+        int_fmt /= 2;
 
     }
     
-    return bin_fmt;
+    bin_fmt
 
+}
+
+/// Convert a number into exactly `width` binary digits, MSB first
+/// 
+/// This is the bit order of the FLAC bitstream. The number is padded
+/// with leading zeros and any bits above `width` are dropped.
+pub fn int_to_bits_msb(int_fmt: u64, width: usize) -> Vec<u8> {
+
+    let mut bin_fmt: Vec<u8> = Vec::with_capacity(width);
+
+    for i in (0..width).rev() {
+
+        if i < 64 {
+            bin_fmt.push(((int_fmt >> i) & 1) as u8);
+        } else {
+            bin_fmt.push(0);
+        }
+
+    }
+
+    bin_fmt
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bits_msb_order() {
+        assert_eq!(int_to_bits_msb(6, 4), vec![0, 1, 1, 0]);
+        assert_eq!(int_to_bits_msb(6, 0), vec![]);
+        assert_eq!(int_to_bin(6), vec![0, 1, 1]);
+    }
+
+    #[test]
+    fn encode_one_byte() {
+        assert_eq!(Utf8Encoder::encode(0), vec![0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(Utf8Encoder::encode(0x7F), vec![0, 1, 1, 1, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn encode_two_bytes() {
+        assert_eq!(
+            Utf8Encoder::encode(0x80),
+            vec![1, 1, 0, 0, 0, 0, 1, 0, 1, 0, 0, 0, 0, 0, 0, 0],
+        );
+    }
 }
\ No newline at end of file
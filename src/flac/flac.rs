@@ -1,5 +1,3 @@
-use std::fs::File;
-
 pub struct FlacWriter;
 
 pub struct FlacFileInfo {
@@ -26,7 +24,7 @@ impl FlacWriter {
 
     pub fn create_flac_info(){
 
-        let flac_file_info = FlacFileInfo::new();
+        let _flac_file_info = FlacFileInfo::new();
 
     }
 
@@ -34,6 +32,12 @@ impl FlacWriter {
 
 }
 
+impl Default for FlacFileInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FlacFileInfo {
 
     pub fn new() -> Self {
@@ -49,6 +53,12 @@ impl FlacFileInfo {
 
 }
 
+impl Default for FlacMeta {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FlacMeta {
 
     pub fn new() -> Self {
@@ -63,6 +73,12 @@ impl FlacMeta {
 
 }
 
+impl Default for FlacAudio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FlacAudio {
 
     pub fn new() -> Self {
@@ -75,4 +91,439 @@ impl FlacAudio {
 
     }
 
+}
+
+/// Get a block size suited to a file of `total_samples` inter-channel samples
+/// 
+/// A block size of 4096 is used for sample rates up to 48kHz and 8192 above
+/// that, which keeps the frames within the streamable subset. The block size
+/// is never larger than `total_samples` so that very short files are written
+/// as a single frame and STREAMINFO reports the block size actually used.
+/// FLAC allows the only frame of a file to be shorter than 16 samples, so
+/// a file with less than 16 samples gets a block size of `total_samples`.
+/// An empty file gets a block size of 1, since a block size must not be 0.
+pub fn recommended_block_size(total_samples: usize, samp_rate: u32) -> usize {
+
+    let block_size = if samp_rate <= 48000 { 4096 } else { 8192 };
+
+    block_size.min(total_samples).max(1)
+
+}
+
+/// Get the Pearson correlation between two channels
+/// 
+/// A value close to 1.0 means the channels are nearly identical and are
+/// good candidates for mid-side stereo, while a value close to -1.0 means
+/// one channel is nearly the inverse of the other. Only the overlapping
+/// samples of `left` and `right` are compared. If either channel is
+/// constant the correlation is undefined and 0.0 is returned.
+pub fn channel_correlation(left: &[i64], right: &[i64]) -> f64 {
+
+    let len = left.len().min(right.len());
+    if len == 0 {
+        return 0.0;
+    }
+
+    let left_mean = left[..len].iter().sum::<i64>() as f64 / len as f64;
+    let right_mean = right[..len].iter().sum::<i64>() as f64 / len as f64;
+
+    let mut covariance = 0.0;
+    let mut left_variance = 0.0;
+    let mut right_variance = 0.0;
+
+    for (l, r) in left.iter().zip(right.iter()) {
+
+        let l_dev = *l as f64 - left_mean;
+        let r_dev = *r as f64 - right_mean;
+
+        covariance += l_dev * r_dev;
+        left_variance += l_dev * l_dev;
+        right_variance += r_dev * r_dev;
+
+    }
+
+    if left_variance == 0.0 || right_variance == 0.0 {
+        return 0.0;
+    }
+
+    covariance / (left_variance * right_variance).sqrt()
+
+}
+
+/// Clamp a sample to the range of a signed `bits`-bit number
+/// 
+/// The range is `[-2^(bits-1), 2^(bits-1)-1]`, so for 16 bits a sample is
+/// clamped to `[-32768, 32767]`. A `bits` of 64 or more leaves the sample
+/// unchanged.
+/// 
+/// # Panics
+/// Panics if `bits` is 0.
+pub fn clamp_to_bits(value: i64, bits: u16) -> i64 {
+
+    assert!(bits > 0, "cannot clamp to 0 bits");
+
+    if bits >= 64 {
+        return value;
+    }
+
+    let max = (1i64 << (bits - 1)) - 1;
+    let min = -(1i64 << (bits - 1));
+
+    value.clamp(min, max)
+
+}
+
+/// Get the smallest signed bit width that can hold every sample of a block
+/// 
+/// A sample fits in `bits` bits if it is within the range described in
+/// `clamp_to_bits()`, so a block whose largest magnitude is 100 needs 8 bits.
+/// A quiet block can need fewer bits than the bit depth of the file, which
+/// makes its verbatim subframe smaller. The result is at least 1, even for
+/// an empty or all-zero block.
+pub fn min_bits_for_block(samples: &[i64]) -> u8 {
+
+    let mut min_bits = 1;
+
+    for sample in samples {
+        // Flip negative samples so both signs count their leading sign bits alike
+        let magnitude = sample ^ (sample >> 63);
+        min_bits = min_bits.max(65 - magnitude.leading_zeros() as u8);
+    }
+
+    min_bits
+
+}
+
+/// Get the bit rate in kbps of `size_bytes` of audio lasting `duration_secs`
+/// 
+/// This uses 1 kbps = 1000 bits per second. Audio with no duration has
+/// a bit rate of 0.0.
+pub fn bitrate_kbps(size_bytes: u64, duration_secs: f64) -> f64 {
+
+    if duration_secs <= 0.0 {
+        return 0.0;
+    }
+
+    (size_bytes * 8) as f64 / duration_secs / 1000.0
+
+}
+
+/// Frame header sample rate codes of the standard sample rates
+/// 
+/// Any other sample rate has to be stored in the frame header itself or,
+/// with code 0, taken from STREAMINFO.
+pub const FLAC_SAMPLE_RATE_CODES: &[(u32, u8)] = &[
+    (88200, 0b0001),
+    (176400, 0b0010),
+    (192000, 0b0011),
+    (8000, 0b0100),
+    (16000, 0b0101),
+    (22050, 0b0110),
+    (24000, 0b0111),
+    (32000, 0b1000),
+    (44100, 0b1001),
+    (48000, 0b1010),
+    (96000, 0b1011),
+];
+
+/// Get the frame header sample rate code of a standard sample rate
+/// 
+/// `None` is returned if `rate` is not in `FLAC_SAMPLE_RATE_CODES`.
+pub fn sample_rate_code(rate: u32) -> Option <u8> {
+
+    FLAC_SAMPLE_RATE_CODES.iter()
+        .find(|(each_rate, _)| *each_rate == rate)
+        .map(|(_, code)| *code)
+
+}
+
+/// Get the frame header sample size code of a bit depth
+/// 
+/// The codes are defined as follows, with `None` returned for any other
+/// bit depth, in which case the frame header has to use code 0 to refer
+/// to the bit depth in STREAMINFO.
+/// 
+/// | Bit depth | Code  |
+/// |-----------|-------|
+/// |     8     | 0b001 |
+/// |    12     | 0b010 |
+/// |    16     | 0b100 |
+/// |    20     | 0b101 |
+/// |    24     | 0b110 |
+/// |    32     | 0b111 |
+pub fn sample_size_code(bps: u16) -> Option <u8> {
+
+    match bps {
+        8 => Some(0b001),
+        12 => Some(0b010),
+        16 => Some(0b100),
+        20 => Some(0b101),
+        24 => Some(0b110),
+        32 => Some(0b111),
+        _ => None,
+    }
+
+}
+
+/// Get the number of the frame that holds a sample
+/// 
+/// This is for streams with a fixed block size of `block_size`, where
+/// frame `n` holds the samples from `n * block_size` up to but excluding
+/// `(n + 1) * block_size`.
+/// 
+/// # Panics
+/// Panics if `block_size` is 0.
+pub fn frame_number_for_sample(sample: u64, block_size: usize) -> u64 {
+
+    assert!(block_size > 0, "block size must not be 0");
+
+    sample / block_size as u64
+
+}
+
+/// Get the first sample of a frame
+/// 
+/// This is the inverse of `frame_number_for_sample()`.
+/// 
+/// # Panics
+/// Panics if `block_size` is 0, since every frame would then start at
+/// sample 0.
+pub fn first_sample_of_frame(frame: u64, block_size: usize) -> u64 {
+
+    assert!(block_size > 0, "block size must not be 0");
+
+    frame * block_size as u64
+
+}
+
+/// Get the number of frames needed for `total_samples` inter-channel samples
+/// 
+/// Every frame holds `block_size` samples except the last one, which holds
+/// whatever is left, so this is `total_samples / block_size` rounded up.
+/// A stream with no samples has no frames.
+/// 
+/// # Panics
+/// Panics if `block_size` is 0 while `total_samples` is not.
+pub fn frame_count(total_samples: usize, block_size: usize) -> usize {
+
+    if total_samples == 0 {
+        return 0;
+    }
+
+    assert!(block_size > 0, "block size must not be 0");
+
+    total_samples.div_ceil(block_size)
+
+}
+
+/// Shift the wasted bits out of the samples of a subframe
+/// 
+/// Wasted bits are low bits that are 0 in every sample of a subframe. The
+/// encoder removes them with an arithmetic right shift before prediction
+/// and stores their count in the subframe header instead.
+/// 
+/// # Panics
+/// Panics if `wasted` is 64 or more.
+pub fn shift_out_wasted(samples: &mut [i64], wasted: u32) {
+
+    assert!(wasted < 64, "cannot shift out 64 or more bits");
+
+    for sample in samples.iter_mut() {
+        *sample >>= wasted;
+    }
+
+}
+
+/// Shift the wasted bits back into the samples of a subframe
+/// 
+/// This is the inverse of `shift_out_wasted()` and is what a decoder does
+/// after reconstructing the samples of a subframe with wasted bits.
+/// 
+/// # Panics
+/// Panics if `wasted` is 64 or more.
+pub fn shift_in_wasted(samples: &mut [i64], wasted: u32) {
+
+    assert!(wasted < 64, "cannot shift in 64 or more bits");
+
+    for sample in samples.iter_mut() {
+        *sample <<= wasted;
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_size_standard() {
+        assert_eq!(recommended_block_size(44100 * 60, 44100), 4096);
+        assert_eq!(recommended_block_size(96000 * 60, 96000), 8192);
+    }
+
+    #[test]
+    fn block_size_short_file() {
+        assert!(recommended_block_size(100, 44100) <= 100);
+    }
+
+    #[test]
+    fn block_size_minimum() {
+        assert_eq!(recommended_block_size(0, 44100), 1);
+        assert_eq!(recommended_block_size(16, 44100), 16);
+    }
+
+    #[test]
+    fn block_size_very_short_file() {
+        assert!(recommended_block_size(5, 44100) <= 5);
+        assert!(recommended_block_size(5, 96000) <= 5);
+    }
+
+    #[test]
+    fn correlation_identical() {
+        let left: Vec <i64> = vec![0, 100, -250, 3000, 12, -7, 800];
+
+        assert!((channel_correlation(&left, &left) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn correlation_inverted() {
+        let left: Vec <i64> = vec![0, 100, -250, 3000, 12, -7, 800];
+        let right: Vec <i64> = left.iter().map(|sample| -sample).collect();
+
+        assert!((channel_correlation(&left, &right) + 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn correlation_constant() {
+        assert_eq!(channel_correlation(&[5, 5, 5], &[1, 2, 3]), 0.0);
+    }
+
+    macro_rules! clamp_tests {
+        ($($name:ident: $value:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let (bits, min, max) = $value;
+
+                assert_eq!(clamp_to_bits(min, bits), min);
+                assert_eq!(clamp_to_bits(max, bits), max);
+                assert_eq!(clamp_to_bits(min - 1, bits), min);
+                assert_eq!(clamp_to_bits(max + 1, bits), max);
+                assert_eq!(clamp_to_bits(0, bits), 0);
+            }
+        )*
+        }
+    }
+
+    clamp_tests! {
+        clamp_8_bits: (8, -128, 127),
+        clamp_16_bits: (16, -32768, 32767),
+        clamp_24_bits: (24, -8_388_608, 8_388_607),
+    }
+
+    #[test]
+    fn clamp_64_bits() {
+        assert_eq!(clamp_to_bits(i64::MIN, 64), i64::MIN);
+        assert_eq!(clamp_to_bits(i64::MAX, 64), i64::MAX);
+    }
+
+    #[test]
+    fn min_bits_known_blocks() {
+        assert_eq!(min_bits_for_block(&[3, -100, 42, 100]), 8);
+        assert_eq!(min_bits_for_block(&[127, -128]), 8);
+        assert_eq!(min_bits_for_block(&[128]), 9);
+        assert_eq!(min_bits_for_block(&[0; 16]), 1);
+        assert_eq!(min_bits_for_block(&[-1]), 1);
+        assert_eq!(min_bits_for_block(&[i64::MIN]), 64);
+    }
+
+    #[test]
+    #[should_panic]
+    fn frame_number_zero_block_size() {
+        frame_number_for_sample(4096, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn first_sample_zero_block_size() {
+        first_sample_of_frame(1, 0);
+    }
+
+    #[test]
+    fn frame_count_exact_multiple() {
+        assert_eq!(frame_count(4096 * 10, 4096), 10);
+        assert_eq!(frame_count(4096, 4096), 1);
+    }
+
+    #[test]
+    fn frame_count_partial_frame() {
+        assert_eq!(frame_count(4096 * 10 + 1, 4096), 11);
+        assert_eq!(frame_count(100, 4096), 1);
+    }
+
+    #[test]
+    fn frame_count_no_samples() {
+        assert_eq!(frame_count(0, 4096), 0);
+        assert_eq!(frame_count(0, recommended_block_size(0, 44100)), 0);
+    }
+
+    #[test]
+    fn wasted_bits_round_trip() {
+        let original: Vec <i64> = vec![16, -32, 0, 4096, -16, 32752, -32768];
+        let mut samples = original.clone();
+
+        shift_out_wasted(&mut samples, 4);
+        assert_eq!(samples, vec![1, -2, 0, 256, -1, 2047, -2048]);
+
+        shift_in_wasted(&mut samples, 4);
+        assert_eq!(samples, original);
+    }
+
+    #[test]
+    fn wasted_bits_zero_shift() {
+        let mut samples: Vec <i64> = vec![3, -5, 7];
+
+        shift_out_wasted(&mut samples, 0);
+        shift_in_wasted(&mut samples, 0);
+        assert_eq!(samples, vec![3, -5, 7]);
+    }
+
+    #[test]
+    fn bitrate_known_size() {
+        // 1 minute at 1411.2 kbps is CD quality audio
+        assert!((bitrate_kbps(10_584_000, 60.0) - 1411.2).abs() < 1e-9);
+        assert!((bitrate_kbps(125_000, 2.0) - 500.0).abs() < 1e-9);
+        assert_eq!(bitrate_kbps(1000, 0.0), 0.0);
+    }
+
+    #[test]
+    fn sample_size_codes() {
+        assert_eq!(sample_size_code(16), Some(0b100));
+        assert_eq!(sample_size_code(20), Some(0b101));
+        assert_eq!(sample_size_code(24), Some(0b110));
+        assert_eq!(sample_size_code(18), None);
+    }
+
+    #[test]
+    fn frame_number_boundaries() {
+        assert_eq!(frame_number_for_sample(0, 4096), 0);
+        assert_eq!(frame_number_for_sample(4095, 4096), 0);
+        assert_eq!(frame_number_for_sample(4096, 4096), 1);
+    }
+
+    #[test]
+    fn first_sample_boundaries() {
+        assert_eq!(first_sample_of_frame(0, 4096), 0);
+        assert_eq!(first_sample_of_frame(1, 4096), 4096);
+        assert_eq!(first_sample_of_frame(frame_number_for_sample(4095, 4096), 4096), 0);
+        assert_eq!(first_sample_of_frame(frame_number_for_sample(4096, 4096), 4096), 4096);
+    }
+
+    #[test]
+    fn sample_rate_codes() {
+        assert_eq!(sample_rate_code(44100), Some(9));
+        assert_eq!(sample_rate_code(22050), Some(6));
+        assert_eq!(sample_rate_code(16000), Some(5));
+        assert_eq!(sample_rate_code(50000), None);
+    }
 }
\ No newline at end of file
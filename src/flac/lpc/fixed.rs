@@ -0,0 +1,152 @@
+pub struct FixedPredictor;
+
+impl FixedPredictor {
+    /// Reconstruct samples from the residuals of a fixed predictor order
+    /// 
+    /// This undoes the fixed predictor and is what a decoder does
+    /// with a fixed subframe. The first `order` samples are the `warmup`
+    /// samples and each following sample is its residual added to the
+    /// prediction made from the samples reconstructed before it.
+    /// 
+    /// # Panics
+    /// Panics if `order` is not within 0 and 4 inclusive or if the length
+    /// of `warmup` is not equal to `order`.
+    pub fn reconstruct_fixed(order: u8, warmup: &[i64], residual: &[i64]) -> Vec <i64> {
+        let coeffs: &[i64] = match order {
+            0 => &[],
+            1 => &[1],
+            2 => &[2, -1],
+            3 => &[3, -3, 1],
+            4 => &[4, -6, 4, -1],
+            _ => panic!("fixed predictor order {} is not within 0 and 4", order),
+        };
+        assert_eq!(warmup.len(), coeffs.len(), "warmup length must equal the predictor order");
+
+        let mut samples = Vec::with_capacity(warmup.len() + residual.len());
+        samples.extend_from_slice(warmup);
+
+        for each_residual in residual {
+            let prediction: i64 = coeffs.iter()
+                .zip(samples.iter().rev())
+                .map(|(coeff, sample)| coeff * sample)
+                .sum();
+            samples.push(each_residual + prediction);
+        }
+
+        samples
+    }
+
+    /// Find the best fixed predictor order together with its residual
+    /// 
+    /// All five orders are evaluated in a single pass, since the residual
+    /// of order `k` is the difference of consecutive residuals of order
+    /// `k - 1`. The best order has the least sum of absolute residuals,
+    /// counted from the fifth sample so that every order is judged on the
+    /// same samples. Ties go to the lower order.
+    /// 
+    /// The residual excludes the first `order` warmup samples, the same
+    /// as the input of `reconstruct_fixed()`.
+    pub fn best_fixed(samples: &[i64]) -> (u8, Vec <i64>) {
+        let max_order = samples.len().min(4);
+        let mut residuals: Vec <Vec <i64>> = (0..=max_order)
+            .map(|order| Vec::with_capacity(samples.len() - order))
+            .collect();
+        let mut sums = vec![0u64; max_order + 1];
+        let mut prev = [0i64; 5];
+
+        for (i, each_sample) in samples.iter().enumerate() {
+            let mut diff = *each_sample;
+
+            for order in 0..=max_order.min(i) {
+                if order > 0 {
+                    let prev_diff = prev[order - 1];
+                    prev[order - 1] = diff;
+                    diff -= prev_diff;
+                }
+                residuals[order].push(diff);
+                if i >= max_order {
+                    sums[order] += diff.unsigned_abs();
+                }
+            }
+            if i < max_order {
+                prev[i] = diff;
+            }
+        }
+
+        let best_order = (0..=max_order).min_by_key(|order| sums[*order]).unwrap();
+        (best_order as u8, residuals.swap_remove(best_order))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn residual_of_order_2(samples: &[i64]) -> Vec <i64> {
+        samples.windows(3)
+            .map(|w| w[2] - 2 * w[1] + w[0])
+            .collect()
+    }
+
+    #[test]
+    fn reconstruct_order_2() {
+        let samples: Vec <i64> = vec![3, 7, -2, 15, 40, 38, -100, 0, 5, 5];
+        let residual = residual_of_order_2(&samples);
+
+        assert_eq!(FixedPredictor::reconstruct_fixed(2, &samples[..2], &residual), samples);
+    }
+
+    #[test]
+    fn reconstruct_order_0() {
+        let samples: Vec <i64> = vec![1, -1, 2, -2];
+
+        assert_eq!(FixedPredictor::reconstruct_fixed(0, &[], &samples), samples);
+    }
+
+    fn naive_residual(samples: &[i64], order: u8) -> Vec <i64> {
+        let mut residual = samples.to_vec();
+        for _ in 0..order {
+            residual = residual.windows(2).map(|w| w[1] - w[0]).collect();
+        }
+        residual
+    }
+
+    #[test]
+    fn best_fixed_matches_naive() {
+        let sine: Vec <i64> = crate::testutil::gen_sine(440.0, 44100, 0.01, 16, 1)
+            .into_iter()
+            .map(|each_sample| each_sample[0])
+            .collect();
+        let noise: Vec <i64> = vec![3, -90, 41, 7, -66, 12, 100, -3, 58, -27];
+        let quadratic: Vec <i64> = (0..64).map(|i| i * i - 5 * i + 2).collect();
+
+        for samples in [sine, noise, quadratic] {
+            let (order, residual) = FixedPredictor::best_fixed(&samples);
+            let sum_abs = |order: u8| -> u64 {
+                naive_residual(&samples, order)[4 - order as usize..].iter().map(|r| r.unsigned_abs()).sum()
+            };
+
+            assert_eq!(residual, naive_residual(&samples, order));
+            assert!((0..=4).all(|other| sum_abs(order) <= sum_abs(other)));
+            assert_eq!(FixedPredictor::reconstruct_fixed(order, &samples[..order as usize], &residual), samples);
+        }
+    }
+
+    #[test]
+    fn best_fixed_polynomial_order() {
+        let quadratic: Vec <i64> = (0..64).map(|i| i * i - 5 * i + 2).collect();
+        let (order, residual) = FixedPredictor::best_fixed(&quadratic);
+
+        // Orders 3 and 4 both predict a quadratic exactly
+        assert_eq!(order, 3);
+        assert!(residual.iter().all(|r| *r == 0));
+        assert_eq!(FixedPredictor::best_fixed(&[]), (0, vec![]));
+        assert_eq!(FixedPredictor::best_fixed(&[9, 9]), (0, vec![9, 9]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn reconstruct_bad_order() {
+        FixedPredictor::reconstruct_fixed(5, &[0; 5], &[]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,89 @@
+pub struct VarPredictor;
+
+impl VarPredictor {
+    /// Get the prediction gain of the best linear predictor in dB
+    /// 
+    /// The gain is the ratio of the signal energy `autoc[0]` to the energy
+    /// of the residual left by the best predictor of order `order`, which
+    /// the Levinson-Durbin recursion gives without computing the predictor
+    /// itself. A gain near 0 dB means that LPC does not predict the signal
+    /// better than sending it as is. Silence has a gain of 0 dB and a
+    /// perfectly predictable signal has an infinite gain.
+    /// 
+    /// # Panics
+    /// Panics if `autoc` has less than `order + 1` lags.
+    pub fn prediction_gain(autoc: &[f64], order: usize) -> f64 {
+        assert!(autoc.len() > order, "autocorrelation has less than order + 1 lags");
+
+        if autoc[0] <= 0.0 {
+            return 0.0;
+        }
+
+        let mut coeffs: Vec <f64> = Vec::with_capacity(order);
+        let mut error = autoc[0];
+
+        for i in 0..order {
+            if error <= 0.0 {
+                return f64::INFINITY;
+            }
+
+            let mut reflection = autoc[i + 1];
+            for (j, coeff) in coeffs.iter().enumerate() {
+                reflection -= coeff * autoc[i - j];
+            }
+            reflection /= error;
+
+            let prev_coeffs = coeffs.clone();
+            for (j, coeff) in coeffs.iter_mut().enumerate() {
+                *coeff -= reflection * prev_coeffs[i - 1 - j];
+            }
+            coeffs.push(reflection);
+            error *= 1.0 - reflection * reflection;
+        }
+
+        if error <= 0.0 {
+            return f64::INFINITY;
+        }
+        10.0 * (autoc[0] / error).log10()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn autocorrelation(data: &[i64], max_lag: usize) -> Vec <f64> {
+        (0..=max_lag)
+            .map(|lag| data[lag..].iter().zip(data).map(|(a, b)| (*a as f64) * (*b as f64)).sum())
+            .collect()
+    }
+
+    #[test]
+    fn gain_of_sine() {
+        let sine: Vec <i64> = crate::testutil::gen_sine(440.0, 44100, 0.1, 16, 1)
+            .into_iter()
+            .map(|each_sample| each_sample[0])
+            .collect();
+
+        assert!(VarPredictor::prediction_gain(&autocorrelation(&sine, 8), 8) > 40.0);
+    }
+
+    #[test]
+    fn gain_of_white_noise() {
+        // Linear congruential generator for reproducible noise
+        let mut state: u32 = 7;
+        let noise: Vec <i64> = (0..16384).map(|_| {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 16) as i64 - 32768
+        }).collect();
+
+        let gain = VarPredictor::prediction_gain(&autocorrelation(&noise, 8), 8);
+        assert!((0.0..0.5).contains(&gain));
+    }
+
+    #[test]
+    fn gain_of_silence() {
+        assert_eq!(VarPredictor::prediction_gain(&[0.0; 5], 4), 0.0);
+        assert_eq!(VarPredictor::prediction_gain(&[4.0, 1.0], 0), 0.0);
+    }
+}
@@ -0,0 +1,327 @@
+use super::cuesheet::CueSheet;
+
+/// Represents a metadata block of a FLAC file
+pub enum MetadataBlock {
+    /// The STREAMINFO block, which must be the first block of the stream
+    StreamInfo(StreamInfo),
+    /// A PADDING block with the given number of zero bytes
+    Padding(u32),
+    /// An APPLICATION block with its registered 4-byte ID and its data
+    Application { id: [u8; 4], data: Vec <u8> },
+    SeekTable(Vec <SeekPoint>),
+    /// A VORBIS_COMMENT block with its vendor string and `NAME=value` comments
+    VorbisComment { vendor: String, comments: Vec <String> },
+    CueSheet(CueSheet),
+    Picture(Picture),
+}
+
+/// Represents the body of a STREAMINFO metadata block
+/// 
+/// The frame sizes are in bytes, with 0 meaning unknown, and `md5` is the
+/// MD5 signature of the unencoded audio, with all zeroes meaning unknown.
+pub struct StreamInfo {
+    pub min_block_size: u16,
+    pub max_block_size: u16,
+    pub min_frame_size: u32,
+    pub max_frame_size: u32,
+    pub samp_rate: u32,
+    pub num_channels: u8,
+    pub bps: u8,
+    pub total_samples: u64,
+    pub md5: [u8; 16],
+}
+
+/// Represents a seek point of a SEEKTABLE metadata block
+/// 
+/// `offset` is in bytes from the first frame to the frame that holds
+/// `sample_number`, and `num_samples` is the number of samples in that
+/// frame. A placeholder point has a `sample_number` of `u64::MAX`.
+pub struct SeekPoint {
+    pub sample_number: u64,
+    pub offset: u64,
+    pub num_samples: u16,
+}
+
+/// Represents a PICTURE metadata block
+/// 
+/// `picture_type` uses the ID3v2 APIC picture types, e.g. 3 for the front
+/// cover. `color_depth` is in bits per pixel and `num_colors` is 0 for
+/// pictures that are not indexed.
+pub struct Picture {
+    pub picture_type: u32,
+    pub mime_type: String,
+    pub description: String,
+    pub width: u32,
+    pub height: u32,
+    pub color_depth: u32,
+    pub num_colors: u32,
+    pub data: Vec <u8>,
+}
+
+impl MetadataBlock {
+    /// Serialize the metadata block, including its 4-byte header
+    /// 
+    /// The last-metadata-block flag of the header is set if `is_last`
+    /// is true.
+    /// 
+    /// # Errors
+    /// `None` is returned if the block body does not fit in the 24-bit
+    /// length of the header, or if a STREAMINFO field does not fit in its
+    /// number of bits.
+    pub fn write(&self, is_last: bool) -> Option <Vec <u8>> {
+
+        let (block_type, body) = match self {
+            MetadataBlock::StreamInfo(stream_info) => (0, stream_info.write_body()?),
+            MetadataBlock::Padding(size) => (1, vec![0; *size as usize]),
+            MetadataBlock::Application { id, data } => (2, [&id[..], data].concat()),
+            MetadataBlock::SeekTable(seek_points) => {
+                let mut body: Vec <u8> = Vec::with_capacity(18 * seek_points.len());
+                for each_point in seek_points {
+                    body.extend_from_slice(&each_point.sample_number.to_be_bytes());
+                    body.extend_from_slice(&each_point.offset.to_be_bytes());
+                    body.extend_from_slice(&each_point.num_samples.to_be_bytes());
+                }
+                (3, body)
+            },
+            MetadataBlock::VorbisComment { vendor, comments } => {
+                // Unlike the rest of FLAC, the lengths here are little-endian
+                let mut body: Vec <u8> = Vec::new();
+                body.extend_from_slice(&u32::try_from(vendor.len()).ok()?.to_le_bytes());
+                body.extend_from_slice(vendor.as_bytes());
+                body.extend_from_slice(&u32::try_from(comments.len()).ok()?.to_le_bytes());
+                for each_comment in comments {
+                    body.extend_from_slice(&u32::try_from(each_comment.len()).ok()?.to_le_bytes());
+                    body.extend_from_slice(each_comment.as_bytes());
+                }
+                (4, body)
+            },
+            MetadataBlock::CueSheet(cue_sheet) => return cue_sheet.write(is_last),
+            MetadataBlock::Picture(picture) => (6, picture.write_body()?),
+        };
+
+        let mut block = block_header(block_type, is_last, body.len())?;
+        block.extend_from_slice(&body);
+
+        Some(block)
+
+    }
+}
+
+impl StreamInfo {
+    /// Serialize the 34-byte body of the STREAMINFO block
+    /// 
+    /// # Errors
+    /// `None` is returned if a frame size does not fit in 24 bits, the
+    /// sample rate in 20 bits or the total samples in 36 bits, or if the
+    /// number of channels is not within 1 and 8 inclusive or the bit depth
+    /// not within 4 and 32 inclusive.
+    fn write_body(&self) -> Option <Vec <u8>> {
+
+        if self.min_frame_size >= 1 << 24 || self.max_frame_size >= 1 << 24
+            || self.samp_rate >= 1 << 20 || self.total_samples >= 1 << 36
+            || !(1..=8).contains(&self.num_channels) || !(4..=32).contains(&self.bps) {
+            return None;
+        }
+
+        let mut body: Vec <u8> = Vec::with_capacity(34);
+        body.extend_from_slice(&self.min_block_size.to_be_bytes());
+        body.extend_from_slice(&self.max_block_size.to_be_bytes());
+        body.extend_from_slice(&self.min_frame_size.to_be_bytes()[1..4]);
+        body.extend_from_slice(&self.max_frame_size.to_be_bytes()[1..4]);
+
+        // 20-bit sample rate, 3-bit channels - 1, 5-bit bps - 1, 36-bit total samples
+        let packed = u64::from(self.samp_rate) << 44
+            | u64::from(self.num_channels - 1) << 41
+            | u64::from(self.bps - 1) << 36
+            | self.total_samples;
+        body.extend_from_slice(&packed.to_be_bytes());
+        body.extend_from_slice(&self.md5);
+
+        Some(body)
+
+    }
+}
+
+impl Picture {
+    /// Serialize the body of the PICTURE block
+    /// 
+    /// # Errors
+    /// `None` is returned if a string or the picture data is longer than
+    /// a 32-bit length allows.
+    fn write_body(&self) -> Option <Vec <u8>> {
+
+        let mut body: Vec <u8> = Vec::new();
+        body.extend_from_slice(&self.picture_type.to_be_bytes());
+        body.extend_from_slice(&u32::try_from(self.mime_type.len()).ok()?.to_be_bytes());
+        body.extend_from_slice(self.mime_type.as_bytes());
+        body.extend_from_slice(&u32::try_from(self.description.len()).ok()?.to_be_bytes());
+        body.extend_from_slice(self.description.as_bytes());
+        body.extend_from_slice(&self.width.to_be_bytes());
+        body.extend_from_slice(&self.height.to_be_bytes());
+        body.extend_from_slice(&self.color_depth.to_be_bytes());
+        body.extend_from_slice(&self.num_colors.to_be_bytes());
+        body.extend_from_slice(&u32::try_from(self.data.len()).ok()?.to_be_bytes());
+        body.extend_from_slice(&self.data);
+
+        Some(body)
+
+    }
+}
+
+/// Serialize a list of metadata blocks in order
+/// 
+/// The last-metadata-block flag is set on the final block of the list
+/// and cleared on every other block. Metadata blocks are whole bytes,
+/// so they are written to a byte vector rather than bit by bit.
+/// 
+/// # Errors
+/// `None` is returned if any block cannot be serialized.
+pub fn write_metadata_blocks(blocks: &[MetadataBlock]) -> Option <Vec <u8>> {
+
+    let mut output: Vec <u8> = Vec::new();
+
+    for (i, each_block) in blocks.iter().enumerate() {
+        output.append(&mut each_block.write(i + 1 == blocks.len())?);
+    }
+
+    Some(output)
+
+}
+
+/// Create the 4-byte header of a metadata block
+/// 
+/// The header holds the last-metadata-block flag, the 7-bit block type
+/// and the 24-bit length of the block body.
+/// 
+/// # Errors
+/// `None` is returned if `body_len` does not fit in 24 bits.
+pub(crate) fn block_header(block_type: u8, is_last: bool, body_len: usize) -> Option <Vec <u8>> {
+
+    if body_len >= 1 << 24 {
+        return None;
+    }
+
+    let mut header: Vec <u8> = Vec::with_capacity(4);
+    header.push(if is_last { 0x80 | block_type } else { block_type });
+    header.extend_from_slice(&(body_len as u32).to_be_bytes()[1..4]);
+
+    Some(header)
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cd_stream_info() -> StreamInfo {
+        StreamInfo {
+            min_block_size: 4096,
+            max_block_size: 4096,
+            min_frame_size: 14,
+            max_frame_size: 0x012345,
+            samp_rate: 44100,
+            num_channels: 2,
+            bps: 16,
+            total_samples: 44100 * 60,
+            md5: [0xAB; 16],
+        }
+    }
+
+    #[test]
+    fn last_flag_on_final_block() {
+        let blocks = vec![
+            MetadataBlock::Application { id: *b"midp", data: vec![1, 2] },
+            MetadataBlock::CueSheet(CueSheet::from_cue_points(&[], 44100)),
+            MetadataBlock::Padding(8),
+        ];
+        let output = write_metadata_blocks(&blocks).unwrap();
+
+        let cue_sheet_start = 4 + 6;
+        let padding_start = output.len() - (4 + 8);
+        assert_eq!(output[0], 2);
+        assert_eq!(output[cue_sheet_start], 5);
+        assert_eq!(output[padding_start], 0x80 | 1);
+        assert_eq!(&output[padding_start + 1..padding_start + 4], &[0, 0, 8]);
+        assert_eq!(&output[4..10], b"midp\x01\x02");
+    }
+
+    #[test]
+    fn no_blocks() {
+        assert!(write_metadata_blocks(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn stream_info_layout() {
+        let block = MetadataBlock::StreamInfo(cd_stream_info()).write(false).unwrap();
+
+        assert_eq!(&block[0..4], &[0, 0, 0, 34]);
+        assert_eq!(&block[4..8], &[0x10, 0x00, 0x10, 0x00]);
+        assert_eq!(&block[8..14], &[0x00, 0x00, 0x0e, 0x01, 0x23, 0x45]);
+        // 44100 Hz, 2 channels, 16 bits and 2646000 samples
+        assert_eq!(&block[14..22], &[0x0a, 0xc4, 0x42, 0xf0, 0x00, 0x28, 0x5f, 0xf0]);
+        assert_eq!(&block[22..38], &[0xAB; 16]);
+    }
+
+    #[test]
+    fn stream_info_out_of_range() {
+        let too_many_samples = StreamInfo { total_samples: 1 << 36, ..cd_stream_info() };
+        let no_channels = StreamInfo { num_channels: 0, ..cd_stream_info() };
+        let too_deep = StreamInfo { bps: 33, ..cd_stream_info() };
+
+        assert!(MetadataBlock::StreamInfo(too_many_samples).write(false).is_none());
+        assert!(MetadataBlock::StreamInfo(no_channels).write(false).is_none());
+        assert!(MetadataBlock::StreamInfo(too_deep).write(false).is_none());
+    }
+
+    #[test]
+    fn seek_table_layout() {
+        let block = MetadataBlock::SeekTable(vec![
+            SeekPoint { sample_number: 0, offset: 0, num_samples: 4096 },
+            SeekPoint { sample_number: u64::MAX, offset: 0, num_samples: 0 },
+        ]).write(false).unwrap();
+
+        assert_eq!(&block[0..4], &[3, 0, 0, 36]);
+        assert_eq!(&block[20..22], &[0x10, 0x00]);
+        assert_eq!(&block[22..30], &[0xff; 8]);
+    }
+
+    #[test]
+    fn vorbis_comment_layout() {
+        let block = MetadataBlock::VorbisComment {
+            vendor: String::from("midp"),
+            comments: vec![String::from("TITLE=A")],
+        }.write(true).unwrap();
+
+        assert_eq!(&block[0..4], &[0x84, 0, 0, 4 + 4 + 4 + 4 + 7]);
+        assert_eq!(&block[4..12], b"\x04\x00\x00\x00midp");
+        assert_eq!(&block[12..16], &[1, 0, 0, 0]);
+        assert_eq!(&block[16..27], b"\x07\x00\x00\x00TITLE=A");
+    }
+
+    #[test]
+    fn picture_layout() {
+        let block = MetadataBlock::Picture(Picture {
+            picture_type: 3,
+            mime_type: String::from("image/png"),
+            description: String::new(),
+            width: 1,
+            height: 1,
+            color_depth: 24,
+            num_colors: 0,
+            data: vec![0x89, 0x50],
+        }).write(false).unwrap();
+
+        assert_eq!(block.len(), 4 + 8 * 4 + 9 + 2);
+        assert_eq!(&block[4..8], &[0, 0, 0, 3]);
+        assert_eq!(&block[8..21], b"\x00\x00\x00\x09image/png");
+        assert_eq!(&block[block.len() - 6..], &[0, 0, 0, 2, 0x89, 0x50]);
+    }
+
+    #[test]
+    fn block_too_long() {
+        assert!(MetadataBlock::Padding(1 << 24).write(false).is_none());
+        assert!(MetadataBlock::Padding((1 << 24) - 1).write(false).is_some());
+        assert!(MetadataBlock::Application { id: *b"midp", data: vec![0; (1 << 24) - 4] }.write(false).is_none());
+        assert!(write_metadata_blocks(&[MetadataBlock::Padding(0), MetadataBlock::Padding(1 << 24)]).is_none());
+    }
+}
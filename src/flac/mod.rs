@@ -0,0 +1,7 @@
+pub mod cuesheet;
+pub mod encoder;
+// `flac.rs` keeps the name it had before the module was built
+#[allow(clippy::module_inception)]
+pub mod flac;
+pub mod lpc;
+pub mod metadata;
@@ -1,5 +1,7 @@
 pub mod wav;
-// pub mod flac;
+pub mod flac;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testutil;
 
 fn main() {
     let file_path = String::from("music_c_mono.wav");
@@ -0,0 +1,41 @@
+use std::f64::consts::PI;
+
+/// Generate a sine wave for use as test input
+/// 
+/// The sine wave has a frequency of `freq` Hz and lasts `secs` seconds at
+/// a sample rate of `samp_rate`. Its amplitude is the largest value a
+/// signed `bits`-bit sample can hold, and every channel carries the same
+/// wave. Each element of the output is one inter-channel sample, just like
+/// the items of `PCMWaveDataChunk`.
+pub fn gen_sine(freq: f64, samp_rate: u32, secs: f64, bits: u16, channels: u16) -> Vec <Vec <i64>> {
+    let amplitude = ((1i64 << (bits - 1)) - 1) as f64;
+    let num_samples = (secs * samp_rate as f64).round() as usize;
+
+    (0..num_samples)
+        .map(|i| {
+            let t = i as f64 / samp_rate as f64;
+            let sample = (amplitude * (2.0 * PI * freq * t).sin()).round() as i64;
+            vec![sample; channels as usize]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sine_length() {
+        assert_eq!(gen_sine(440.0, 44100, 1.5, 16, 2).len(), 66150);
+        assert_eq!(gen_sine(440.0, 8000, 0.25, 16, 1).len(), 2000);
+    }
+
+    #[test]
+    fn sine_peak() {
+        let samples = gen_sine(1000.0, 48000, 0.1, 16, 2);
+
+        let peak = samples.iter().flatten().map(|sample| sample.abs()).max();
+        assert_eq!(peak, Some(32767));
+        assert!(samples.iter().all(|each_sample| each_sample.len() == 2 && each_sample[0] == each_sample[1]));
+    }
+}
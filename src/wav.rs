@@ -2,15 +2,36 @@ use core::fmt;
 use std::fs::File;
 use std::path::Path;
 use std::error;
-use std::io::{self, Read, Seek, SeekFrom};
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::iter::FusedIterator;
+use std::collections::{BTreeMap, VecDeque};
+use std::cell::RefCell;
+use std::rc::Rc;
 
-use byteorder::{ByteOrder, LittleEndian};
+use byteorder::{BigEndian, ByteOrder, LittleEndian, WriteBytesExt};
+#[cfg(feature = "mmap")]
+use memmap2::Mmap;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::flac::flac;
 
 /// Represents a PCM WAV file
-pub struct PCMWaveInfo {
+/// 
+/// The audio data is read from a source of type `R`, which is the
+/// opened file for WAV files opened with `WaveReader::open_pcm()`.
+/// Chunks that are not otherwise read are kept in `metadata_chunks`,
+/// where the bodies of chunks with the same id are in file order.
+pub struct PCMWaveInfo <R = File> {
     pub riff_header: RiffChunk,
     pub fmt_header: PCMWaveFormatChunk,
-    pub data_chunks: Vec <PCMWaveDataChunk>,
+    pub data_chunks: Vec <PCMWaveDataChunk <R>>,
+    pub cue_points: Vec <CuePoint>,
+    pub instrument: Option <Instrument>,
+    pub ixml: Option <XmlMetadata>,
+    pub axml: Option <XmlMetadata>,
+    pub metadata_chunks: BTreeMap <[u8; 4], Vec <Vec <u8>>>,
+    pub tags: BTreeMap <String, String>,
 }
 
 /// Represents a RIFF chnk from a WAV file
@@ -27,22 +48,93 @@ pub struct RiffChunk {
 /// `fmt_` where `_` is a space (0x20 in hex) and then followed by
 /// 20 bytes of metadata denoting information about the audio file
 /// itself such as the sample and bit rates.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 pub struct PCMWaveFormatChunk {
+    pub audio_format: u16,
     pub num_channels: u16,
     pub samp_rate: u32,
     pub bps: u16,
 }
 
+/// Represents how the samples of a WAV file are stored
+/// 
+/// Integer samples are stored in the smallest whole number of bytes that
+/// fits their bit depth. Samples narrower than their container, such as
+/// 20-bit samples in 3 bytes, are left-justified. 8-bit samples are
+/// stored unsigned and wider ones signed, but all of them are decoded
+/// to signed samples.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SampleFormat {
+    U8,
+    S16,
+    S24,
+    S32,
+    /// 8-bit A-law (G.711) samples
+    ALaw,
+    /// 8-bit u-law (G.711) samples
+    ULaw,
+}
+
 /// Represents a data chunk from a WAV file
 /// 
 /// A data chunk in a WAV file starts with a magic string `data` and then
 /// followed by the number of samples that follow and then finally the
 /// audio data samples themselves.
-pub struct PCMWaveDataChunk {
+pub struct PCMWaveDataChunk <R = File> {
     pub size_bytes: u32,
+    pub data_start: u64,
     pub format: PCMWaveFormatChunk,
-    pub data_buf: io::BufReader<File>,
+    pub data_buf: io::BufReader<SharedReader <R>>,
+    pub is_big_endian: bool,
+    bytes_read: u32,
+    exhausted: bool,
+}
+
+/// Represents a cue point from the `cue ` chunk of a WAV file
+/// 
+/// Cue points mark positions in the audio, such as track starts or
+/// markers placed by an editor. The `sample_offset` is the inter-channel
+/// sample the cue point refers to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CuePoint {
+    pub id: u32,
+    pub sample_offset: u32,
+}
+
+/// Represents the `inst` chunk of a WAV file
+/// 
+/// Samplers use it to map the audio to a range of notes and velocities.
+/// The `unshifted_note` is the MIDI note played back at the original pitch,
+/// `fine_tune` is in cents and `gain` is in decibels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Instrument {
+    pub unshifted_note: u8,
+    pub fine_tune: i8,
+    pub gain: i8,
+    pub low_note: u8,
+    pub high_note: u8,
+    pub low_velocity: u8,
+    pub high_velocity: u8,
+}
+
+/// Represents the XML document of an `iXML` or `axml` chunk
+/// 
+/// The XML is kept as is and not interpreted. A chunk that is not valid
+/// UTF-8 is kept as raw bytes instead, so it can still be written back.
+#[derive(Clone, Debug, PartialEq)]
+pub enum XmlMetadata {
+    Text(String),
+    Bytes(Vec <u8>),
+}
+
+/// Represents a handle to a reader shared by several data chunks
+/// 
+/// Every handle keeps its own position and seeks the shared reader to it
+/// before each read, so the data chunks of a file can be read in any
+/// order from a single reader.
+pub struct SharedReader <R> {
+    reader: Rc <RefCell <R>>,
+    pos: u64,
 }
 
 /// Represents an iterator to a data chunk from a WAV file
@@ -50,9 +142,28 @@ pub struct PCMWaveDataChunk {
 /// This struct is not instantiated by itself and is generated
 /// by calling the methods `PCMWaveDataChunk::chunks_byte_rate()`
 /// and `PCMWaveDataChunk::chunks()`.
-pub struct PCMWaveDataChunkWindow {
+pub struct PCMWaveDataChunkWindow <R = File> {
     chunk_size: usize,
-    data_chunk: PCMWaveDataChunk, // Borrow a mutable reference
+    data_chunk: PCMWaveDataChunk <R>, // Borrow a mutable reference
+}
+
+/// Represents an iterator over overlapping windows of a data chunk
+/// 
+/// Each window has `size` inter-channel samples and starts `hop` samples
+/// after the previous one, so consecutive windows share `size - hop`
+/// samples. The last window may be shorter than `size`.
+pub struct PCMWaveDataChunkOverlapWindow <R = File> {
+    size: usize,
+    hop: usize,
+    window: VecDeque <i64>,
+    data_chunk: PCMWaveDataChunk <R>,
+}
+
+/// Represents the header of a chunk found while walking the chunk list
+struct ChunkHeader {
+    id: [u8; 4],
+    pos: u64,
+    size_bytes: u32,
 }
 
 /// Represents a WAV reader
@@ -62,27 +173,324 @@ pub struct WaveReader;
 pub enum WaveReaderError {
     NotRiffError,
     NotWaveError,
+    ChannelIndexError,
     NotPCMError,
     ChunkTypeError,
     DataAlignmentError,
+    DuplicateChunkError,
+    FormatMismatchError,
+    NonCanonical,
+    SampleRangeError,
+    SampleRateError,
+    TruncatedFrameError,
+    UnsupportedFormat(u16),
+    UnexpectedEof,
     ReadError,
 }
 
 impl WaveReader {
-    pub fn open_pcm(file_path: &str) -> Result <PCMWaveInfo, WaveReaderError> {
-        let mut fh = File::open(Path::new(file_path))?;
+    /// Open a PCM WAV file
+    /// 
+    /// Every `data` chunk of the file is read into `data_chunks`, each
+    /// through its own handle to the file.
+    pub fn open_pcm<P: AsRef<Path>>(file_path: P) -> Result <PCMWaveInfo, WaveReaderError> {
+        let file_path = file_path.as_ref();
+        Self::read_pcm_with(SharedReader::new(File::open(file_path)?), || File::open(file_path).map(SharedReader::new))
+    }
+
+    /// Open a PCM WAV file through a memory map
+    /// 
+    /// This reads the same way as `open_pcm()` but from a memory mapping
+    /// of the file instead of buffered reads, which is faster for very
+    /// large files.
+    /// 
+    /// The file must not be modified while it is mapped, since the
+    /// mapping would then change under the reader.
+    #[cfg(feature = "mmap")]
+    pub fn open_pcm_mmap<P: AsRef<Path>>(file_path: P) -> Result <PCMWaveInfo <Cursor <Mmap>>, WaveReaderError> {
+        let fh = File::open(file_path)?;
+        // SAFETY: The mapping is read-only, and modifying the file while it
+        // is being read is documented as unsupported above.
+        let map = unsafe { Mmap::map(&fh) }?;
+
+        Self::read_pcm(Cursor::new(map))
+    }
+
+    /// Check whether `bytes` hold a complete and readable PCM WAV file
+    /// 
+    /// The bytes are parsed and all of their samples are read, and any
+    /// error along the way, including a data chunk that is cut short,
+    /// makes this return `false`.
+    pub fn is_valid_wav(bytes: &[u8]) -> bool {
+        let wave_info = match Self::read_pcm(Cursor::new(bytes)) {
+            Ok(wave_info) => wave_info,
+            Err(_) => return false,
+        };
+
+        for mut each_chunk in wave_info.data_chunks {
+            loop {
+                match each_chunk.try_next() {
+                    Ok(Some(_)) => continue,
+                    Ok(None) => break,
+                    Err(_) => return false,
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Read a PCM WAV file from any seekable reader
+    /// 
+    /// This reads from memory, e.g. a `Cursor`, the same way `open_pcm()`
+    /// reads from a file. All data chunks read their samples from `fh`
+    /// through a `SharedReader`.
+    pub fn read_pcm<R: Read + Seek>(fh: R) -> Result <PCMWaveInfo <R>, WaveReaderError> {
+        let fh = SharedReader::new(fh);
+        let shared = fh.clone();
+        Self::read_pcm_with(fh, || Ok(shared.clone()))
+    }
+
+    /// Read a PCM WAV file from `fh`, opening more readers with `reopen`
+    /// 
+    /// The chunk list is walked once and every chunk is read from the
+    /// positions found. The headers and the first `data` chunk are read
+    /// from `fh`, and every other `data` chunk from a reader returned by
+    /// `reopen`, which must read the same bytes as `fh`.
+    /// Chunks other than `fmt ` and `data` are optional, so one that cannot
+    /// be read or parsed is left out instead of failing the whole file.
+    fn read_pcm_with<R: Read + Seek, F: FnMut() -> io::Result <SharedReader <R>>>(mut fh: SharedReader <R>, mut reopen: F) -> Result <PCMWaveInfo <R>, WaveReaderError> {
         let riff_header = Self::read_riff_chunk(&mut fh)?;
-        let fmt_header = Self::read_fmt_chunk(&mut fh)?;
-        let data_chunks = vec![Self::read_data_chunk(36, &fmt_header, fh)?]; // Adjust the starting position as necessary
+        let is_big_endian = riff_header.is_big_endian;
+        let chunks = Self::read_chunk_list(&mut fh, is_big_endian)?;
+
+        let mut fmt_chunks = chunks.iter().filter(|chunk| &chunk.id == b"fmt ");
+        fh.seek(SeekFrom::Start(fmt_chunks.next().ok_or(WaveReaderError::UnexpectedEof)?.pos))?;
+        let fmt_header = Self::read_fmt_chunk(&mut fh, is_big_endian)?;
+        if fmt_chunks.next().is_some() {
+            return Err(WaveReaderError::DuplicateChunkError);
+        }
+
+        let mut cue_points = None;
+        let mut instrument = None;
+        let mut ixml = None;
+        let mut axml = None;
+        let mut metadata_chunks = BTreeMap::new();
+        for each_chunk in &chunks {
+            if &each_chunk.id == b"fmt " || &each_chunk.id == b"data" {
+                continue;
+            }
+
+            // Optional chunks are read best-effort, so a truncated or malformed
+            // one is left out instead of making the audio unreadable
+            let body = match Self::read_chunk_body(&mut fh, each_chunk) {
+                Ok(body) => body,
+                Err(_) => continue,
+            };
+            match &each_chunk.id {
+                b"cue " if cue_points.is_none() => cue_points = Self::parse_cue_chunk(&body, is_big_endian),
+                b"inst" if instrument.is_none() => instrument = Self::parse_inst_chunk(&body),
+                b"iXML" if ixml.is_none() => ixml = Some(Self::parse_xml_chunk(body)),
+                b"axml" if axml.is_none() => axml = Some(Self::parse_xml_chunk(body)),
+                b"cue " | b"inst" | b"iXML" | b"axml" => {},
+                _ => {
+                    metadata_chunks.entry(each_chunk.id).or_insert_with(Vec::new).push(body);
+                },
+            }
+        }
+        let cue_points = cue_points.unwrap_or_default();
+        let tags = read_id3_tags(&metadata_chunks);
+
+        let data_positions: Vec <u64> = chunks.iter()
+            .filter(|chunk| &chunk.id == b"data")
+            .map(|chunk| chunk.pos)
+            .collect();
+        if data_positions.is_empty() {
+            return Err(WaveReaderError::UnexpectedEof);
+        }
+        let mut data_chunks = Vec::with_capacity(data_positions.len());
+        for each_pos in &data_positions[1..] {
+            data_chunks.push(Self::read_shared_data_chunk(*each_pos, &fmt_header, reopen()?, is_big_endian)?);
+        }
+        data_chunks.insert(0, Self::read_shared_data_chunk(data_positions[0], &fmt_header, fh, is_big_endian)?);
 
         Ok(PCMWaveInfo {
             riff_header,
             fmt_header,
             data_chunks,
+            cue_points,
+            instrument,
+            ixml,
+            axml,
+            metadata_chunks,
+            tags,
+        })
+    }
+
+    /// Open a PCM WAV file with the canonical 44-byte header layout
+    /// 
+    /// Unlike `open_pcm()`, this requires the `fmt ` chunk to immediately
+    /// follow the RIFF chunk with a 16-byte body, and the `data` chunk to
+    /// immediately follow the `fmt ` chunk. Chunks after that, including
+    /// more `data` chunks, are read the same way as by `open_pcm()`.
+    /// 
+    /// # Errors
+    /// `NonCanonical` is returned if the file deviates from this layout,
+    /// even if it could otherwise be read by `open_pcm()`.
+    pub fn open_pcm_strict<P: AsRef<Path>>(file_path: P) -> Result <PCMWaveInfo, WaveReaderError> {
+        let file_path = file_path.as_ref();
+        let mut fh = File::open(file_path)?;
+        Self::check_canonical(&mut fh)?;
+        fh.seek(SeekFrom::Start(0))?;
+
+        Self::read_pcm(fh)
+    }
+
+    /// Check that `fh` has the canonical 44-byte header layout
+    /// 
+    /// # Errors
+    /// `NonCanonical` is returned if the `fmt ` chunk does not immediately
+    /// follow the RIFF chunk with a 16-byte body, or if the `data` chunk
+    /// does not immediately follow the `fmt ` chunk.
+    fn check_canonical<R: Read + Seek>(fh: &mut R) -> Result <(), WaveReaderError> {
+        let is_big_endian = Self::read_riff_chunk(fh)?.is_big_endian;
+
+        let mut buffer = [0u8; 8];
+        fh.read_exact(&mut buffer)?;
+        if &buffer[0..4] != b"fmt " || Self::read_u32(&buffer[4..8], is_big_endian) != 16 {
+            return Err(WaveReaderError::NonCanonical);
+        }
+
+        fh.seek(SeekFrom::Start(36))?;
+        fh.read_exact(&mut buffer)?;
+        if &buffer[0..4] != b"data" {
+            return Err(WaveReaderError::NonCanonical);
+        }
+
+        Ok(())
+    }
+
+    /// Walk the chunk list once and record the header of every chunk
+    /// 
+    /// The walk starts after the RIFF chunk header and skips the padding
+    /// byte that follows a chunk with an odd size. A chunk with the unknown
+    /// size of a streaming WAV file extends to the end of the file, so the
+    /// walk stops after it.
+    fn read_chunk_list<R: Read + Seek>(fh: &mut R, is_big_endian: bool) -> Result <Vec <ChunkHeader>, WaveReaderError> {
+        let mut chunks = Vec::new();
+        let mut pos = fh.seek(SeekFrom::Start(12))?;
+        let mut buffer = [0u8; 8];
+
+        loop {
+            if let Err(e) = fh.read_exact(&mut buffer) {
+                return match e.kind() {
+                    io::ErrorKind::UnexpectedEof => Ok(chunks),
+                    _ => Err(e.into()),
+                };
+            }
+
+            let size_bytes = Self::read_u32(&buffer[4..8], is_big_endian);
+            chunks.push(ChunkHeader {
+                id: buffer[0..4].try_into().unwrap(),
+                pos,
+                size_bytes,
+            });
+            if size_bytes == 0xFFFFFFFF {
+                return Ok(chunks);
+            }
+
+            pos += 8 + u64::from(size_bytes) + u64::from(size_bytes % 2);
+            fh.seek(SeekFrom::Start(pos))?;
+        }
+    }
+
+    /// Read the body of a chunk found by `read_chunk_list()`
+    /// 
+    /// # Errors
+    /// `ReadError` is returned if the file ends before the whole body.
+    fn read_chunk_body<R: Read + Seek>(fh: &mut R, chunk: &ChunkHeader) -> Result <Vec <u8>, WaveReaderError> {
+        fh.seek(SeekFrom::Start(chunk.pos + 8))?;
+
+        let mut body = Vec::new();
+        fh.by_ref().take(u64::from(chunk.size_bytes)).read_to_end(&mut body)?;
+        if body.len() != chunk.size_bytes as usize {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+
+        Ok(body)
+    }
+
+    /// Parse the cue points from the body of a `cue ` chunk
+    /// 
+    /// `None` is returned if the body is too short for the number of cue
+    /// points it declares.
+    fn parse_cue_chunk(body: &[u8], is_big_endian: bool) -> Option <Vec <CuePoint>> {
+        let num_cue_points = Self::read_u32(body.get(0..4)?, is_big_endian) as usize;
+        let cue_bytes = &body[4..];
+        if cue_bytes.len() / 24 < num_cue_points {
+            return None;
+        }
+
+        Some(cue_bytes.chunks_exact(24).take(num_cue_points)
+            .map(|cue_buffer| CuePoint {
+                id: Self::read_u32(&cue_buffer[0..4], is_big_endian),
+                sample_offset: Self::read_u32(&cue_buffer[20..24], is_big_endian),
+            })
+            .collect())
+    }
+
+    /// Parse the XML document from the body of an `iXML` or `axml` chunk
+    /// 
+    /// Trailing NUL bytes, which some writers use as padding, are removed.
+    /// The document is kept as bytes if it is not valid UTF-8.
+    fn parse_xml_chunk(mut xml_bytes: Vec <u8>) -> XmlMetadata {
+        while xml_bytes.last() == Some(&0) {
+            xml_bytes.pop();
+        }
+
+        match String::from_utf8(xml_bytes) {
+            Ok(xml) => XmlMetadata::Text(xml),
+            Err(e) => XmlMetadata::Bytes(e.into_bytes()),
+        }
+    }
+
+    /// Parse the instrument parameters from the body of an `inst` chunk
+    /// 
+    /// `None` is returned if the body is shorter than the 7 bytes of
+    /// parameters.
+    fn parse_inst_chunk(body: &[u8]) -> Option <Instrument> {
+        let params = body.get(0..7)?;
+
+        Some(Instrument {
+            unshifted_note: params[0],
+            fine_tune: params[1] as i8,
+            gain: params[2] as i8,
+            low_note: params[3],
+            high_note: params[4],
+            low_velocity: params[5],
+            high_velocity: params[6],
         })
     }
 
-    fn read_riff_chunk(fh: &mut File) -> Result <RiffChunk, WaveReaderError> {
+    /// Read a 16-bit integer in the byte order of the file
+    fn read_u16(buffer: &[u8], is_big_endian: bool) -> u16 {
+        if is_big_endian {
+            BigEndian::read_u16(buffer)
+        } else {
+            LittleEndian::read_u16(buffer)
+        }
+    }
+
+    /// Read a 32-bit integer in the byte order of the file
+    fn read_u32(buffer: &[u8], is_big_endian: bool) -> u32 {
+        if is_big_endian {
+            BigEndian::read_u32(buffer)
+        } else {
+            LittleEndian::read_u32(buffer)
+        }
+    }
+
+    fn read_riff_chunk<R: Read>(fh: &mut R) -> Result <RiffChunk, WaveReaderError> {
         let mut buffer = [0u8; 12];
         fh.read_exact(&mut buffer)?;
 
@@ -92,11 +500,7 @@ impl WaveReader {
 
         let is_big_endian = &buffer[0..4] == b"RIFX"; 
 
-        let file_size = if is_big_endian {
-            u32::from_be_bytes(buffer[4..8].try_into().unwrap())
-        } else {
-            LittleEndian::read_u32(&buffer[4..8])
-        };
+        let file_size = Self::read_u32(&buffer[4..8], is_big_endian);
 
         if &buffer[8..12] != b"WAVE" { // "WAVE"
         return Err(WaveReaderError::NotWaveError);
@@ -104,11 +508,11 @@ impl WaveReader {
 
         Ok(RiffChunk {
             file_size,
-            is_big_endian: is_big_endian, 
+            is_big_endian, 
         })
     }
 
-    fn read_fmt_chunk(fh: &mut File) -> Result <PCMWaveFormatChunk, WaveReaderError> {
+    fn read_fmt_chunk<R: Read + Seek>(fh: &mut R, is_big_endian: bool) -> Result <PCMWaveFormatChunk, WaveReaderError> {
         let mut buffer = [0u8; 24];
         fh.read_exact(&mut buffer)?;
 
@@ -117,39 +521,77 @@ impl WaveReader {
             return Err(WaveReaderError::ChunkTypeError);
         }
 
-        let audio_format = LittleEndian::read_u16(&buffer[8..10]);
-        if audio_format != 1 { // PCM
-            return Err(WaveReaderError::NotPCMError);
+        let size_bytes = Self::read_u32(&buffer[4..8], is_big_endian);
+        if size_bytes > 16 { // Skip the extension of non-PCM style fmt chunks
+            fh.seek(SeekFrom::Current(i64::from(size_bytes - 16 + size_bytes % 2)))?;
         }
 
-        let num_channels = LittleEndian::read_u16(&buffer[10..12]);
-        let samp_rate = LittleEndian::read_u32(&buffer[12..16]);
-        let bps = LittleEndian::read_u16(&buffer[22..24]);
+        let audio_format = Self::read_u16(&buffer[8..10], is_big_endian);
+        let num_channels = Self::read_u16(&buffer[10..12], is_big_endian);
+        let samp_rate = Self::read_u32(&buffer[12..16], is_big_endian);
+        let bps = Self::read_u16(&buffer[22..24], is_big_endian);
+
+        match audio_format {
+            1 if (1..=32).contains(&bps) => {}, // PCM
+            6 | 7 if bps == 8 => {}, // A-law, u-law
+            // PCM of other bit depths, ADPCM, IEEE float, A-law, u-law, IMA ADPCM,
+            // GSM, MPEG, MP3, extensible
+            1 | 2 | 3 | 6 | 7 | 0x11 | 0x31 | 0x50 | 0x55 | 0xFFFE => {
+                return Err(WaveReaderError::UnsupportedFormat(audio_format));
+            },
+            _ => return Err(WaveReaderError::NotPCMError),
+        }
+
+        if samp_rate == 0 {
+            return Err(WaveReaderError::SampleRateError);
+        }
 
         Ok(PCMWaveFormatChunk {
+            audio_format,
             num_channels,
             samp_rate,
             bps,
         })
     }
 
-    fn read_data_chunk(start_pos: u64, fmt_info: &PCMWaveFormatChunk, mut fh: File) -> Result<PCMWaveDataChunk, WaveReaderError> {
+    fn read_data_chunk<R: Read + Seek>(start_pos: u64, fmt_info: &PCMWaveFormatChunk, fh: R, is_big_endian: bool) -> Result<PCMWaveDataChunk <R>, WaveReaderError> {
+        Self::read_shared_data_chunk(start_pos, fmt_info, SharedReader::new(fh), is_big_endian)
+    }
+
+    fn read_shared_data_chunk<R: Read + Seek>(start_pos: u64, fmt_info: &PCMWaveFormatChunk, fh: SharedReader <R>, is_big_endian: bool) -> Result<PCMWaveDataChunk <R>, WaveReaderError> {
         let mut buf_reader = io::BufReader::new(fh);
         buf_reader.seek(SeekFrom::Start(start_pos))?;
     
         let mut buffer = [0u8; 8];
         buf_reader.read_exact(&mut buffer)?;
         let chunk_id = LittleEndian::read_u32(&buffer[0..4]);
-        let size_bytes = LittleEndian::read_u32(&buffer[4..8]);
+        let mut size_bytes = Self::read_u32(&buffer[4..8], is_big_endian);
     
         if chunk_id != 0x61746164 { // "data"
             return Err(WaveReaderError::ChunkTypeError);
         }
+
+        let data_start = start_pos + 8;
+        if size_bytes == 0xFFFFFFFF { // Streaming WAV, size unknown at write time
+            let file_len = buf_reader.seek(SeekFrom::End(0))?;
+            size_bytes = u32::try_from(file_len.saturating_sub(data_start)).unwrap_or(u32::MAX);
+            size_bytes -= size_bytes % u32::from(fmt_info.block_align().max(1));
+            buf_reader.seek(SeekFrom::Start(data_start))?;
+        }
+
+        let block_align = u32::from(fmt_info.block_align());
+        if block_align == 0 || size_bytes % block_align != 0 {
+            return Err(WaveReaderError::DataAlignmentError);
+        }
     
         Ok(PCMWaveDataChunk {
             size_bytes,
+            data_start,
             format: *fmt_info,
             data_buf: buf_reader,
+            is_big_endian,
+            bytes_read: 0,
+            exhausted: false,
         })
     }
     
@@ -163,9 +605,18 @@ impl fmt::Display for WaveReaderError {
         match self {
             WaveReaderError::NotRiffError => write!(f, "Not a RIFF format error"),
             WaveReaderError::NotWaveError => write!(f, "Not a Wave file error"),
+            WaveReaderError::ChannelIndexError => write!(f, "Channel index out of range error"),
             WaveReaderError::NotPCMError => write!(f, "Not a PCM data error"),
             WaveReaderError::ChunkTypeError => write!(f, "Invalid chunk type error"),
             WaveReaderError::DataAlignmentError => write!(f, "Data alignment error"),
+            WaveReaderError::DuplicateChunkError => write!(f, "Duplicate chunk error"),
+            WaveReaderError::FormatMismatchError => write!(f, "Mismatched data chunk format error"),
+            WaveReaderError::NonCanonical => write!(f, "Non-canonical WAV layout error"),
+            WaveReaderError::SampleRangeError => write!(f, "Sample out of range for the bit depth error"),
+            WaveReaderError::SampleRateError => write!(f, "Sample rate of 0 Hz error"),
+            WaveReaderError::UnsupportedFormat(audio_format) => write!(f, "Unsupported audio format {} error", audio_format),
+            WaveReaderError::TruncatedFrameError => write!(f, "Data ends in the middle of a sample error"),
+            WaveReaderError::UnexpectedEof => write!(f, "Chunk not found before the end of the file error"),
             WaveReaderError::ReadError => write!(f, "Error reading from file."),
         }
     }
@@ -181,17 +632,213 @@ impl From<io::Error> for WaveReaderError {
     }
 }
 
-impl fmt::Display for PCMWaveInfo {
+impl <R> fmt::Display for PCMWaveInfo <R> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "WAVE File {:?} bytes, {:?}-bit {:?} channels, {:?}Hz, {:?} data chunks", self.riff_header.file_size, self.fmt_header.bps, self.fmt_header.num_channels, self.fmt_header.samp_rate, self.data_chunks.len())
     }
 }
 
+impl <R: Read + Seek> PCMWaveInfo <R> {
+    /// Iterate over the samples of all data chunks as one stream
+    /// 
+    /// The data chunks are read one after another in the order they
+    /// appear in `data_chunks`.
+    /// 
+    /// # Errors
+    /// `FormatMismatchError` is returned if any of the data chunks has
+    /// a format different from the format chunk of the file.
+    pub fn samples(self) -> Result <impl Iterator<Item = Vec<i64>>, WaveReaderError> {
+        let fmt_header = self.fmt_header;
+
+        if self.data_chunks.iter().any(|chunk| chunk.format != fmt_header) {
+            return Err(WaveReaderError::FormatMismatchError);
+        }
+
+        Ok(self.data_chunks.into_iter().flatten())
+    }
+
+    /// Check the RIFF file size against the actual length of the file
+    /// 
+    /// The file size in the RIFF chunk excludes the 8 bytes of the `RIFF`
+    /// magic string and the size itself, so it should be `actual_len - 8`.
+    /// Streaming and corrupt files often have a wrong file size.
+    pub fn file_size_matches(&self, actual_len: u64) -> bool {
+        actual_len >= 8 && u64::from(self.riff_header.file_size) == actual_len - 8
+    }
+
+    /// Get the number of FLAC frames this file would be encoded into
+    /// 
+    /// This counts the inter-channel samples of all data chunks and uses
+    /// the block size from `recommended_block_size()`.
+    pub fn frame_count(&self) -> usize {
+        let total_samples: usize = self.data_chunks.iter()
+            .map(|chunk| (chunk.size_bytes / u32::from(chunk.format.block_align())) as usize)
+            .sum();
+        let block_size = flac::recommended_block_size(total_samples, self.fmt_header.samp_rate);
+
+        flac::frame_count(total_samples, block_size)
+    }
+
+    /// Get the bit rate in kbps of encoding this file into `encoded_bytes`
+    /// 
+    /// The duration is taken from the inter-channel samples of all data
+    /// chunks and the sample rate, which are counted in `u64` so that data
+    /// chunks of any size are supported.
+    pub fn bitrate_kbps(&self, encoded_bytes: u64) -> f64 {
+        let total_samples: u64 = self.data_chunks.iter()
+            .map(|chunk| u64::from(chunk.num_frames()))
+            .sum();
+        let duration_secs = total_samples as f64 / f64::from(self.fmt_header.samp_rate);
+
+        flac::bitrate_kbps(encoded_bytes, duration_secs)
+    }
+
+    /// Read all samples of one channel
+    /// 
+    /// This reads the whole file through `samples()` and keeps only the
+    /// samples of the channel at `index`, starting from 0.
+    /// 
+    /// # Errors
+    /// `ChannelIndexError` is returned if `index` is not less than the
+    /// number of channels, and `FormatMismatchError` as in `samples()`.
+    pub fn channel_samples(self, index: usize) -> Result <Vec <i64>, WaveReaderError> {
+        if index >= self.fmt_header.num_channels as usize {
+            return Err(WaveReaderError::ChannelIndexError);
+        }
+
+        Ok(self.samples()?.map(|each_sample| each_sample[index]).collect())
+    }
+
+    /// Check whether every channel carries the same audio
+    /// 
+    /// Such files, usually stereo files of a mono recording, can be
+    /// downmixed to a single channel without losing anything. The samples
+    /// are compared until the first difference. A file with less than 2
+    /// channels, or one that `samples()` cannot read, is not dual mono.
+    pub fn is_dual_mono(self) -> bool {
+        if self.fmt_header.num_channels < 2 {
+            return false;
+        }
+
+        match self.samples() {
+            Ok(mut samples) => samples.all(|each_sample| each_sample.iter().all(|sample| *sample == each_sample[0])),
+            Err(_) => false,
+        }
+    }
+}
+
+impl PCMWaveInfo <Cursor <Vec <u8>>> {
+    /// Create a WAV file in memory from inter-channel samples
+    /// 
+    /// The samples are laid out in one data chunk the same way the reader
+    /// expects them, so iterating over the result gives back `samples`.
+    /// This allows feeding synthetic audio to code that works with
+    /// `PCMWaveInfo` without writing a file first.
+    /// 
+    /// # Errors
+    /// `FormatMismatchError` is returned if a sample does not have exactly
+    /// `fmt.num_channels` channels, `SampleRangeError` if a sample does
+    /// not fit in `fmt.bps` bits, and `UnsupportedFormat` if `fmt` uses a
+    /// companded format or a bit depth the reader cannot decode.
+    pub fn from_samples(samples: Vec <Vec <i64>>, fmt: PCMWaveFormatChunk) -> Result <Self, WaveReaderError> {
+        if fmt.audio_format != 1 || fmt.bps == 0 || fmt.bps > 32 {
+            return Err(WaveReaderError::UnsupportedFormat(fmt.audio_format));
+        }
+        let shift = 8 * u32::from(fmt.bytes_per_sample()) - u32::from(fmt.bps);
+        let (min, max) = fmt.sample_range();
+
+        let size_bytes = samples.len() * fmt.block_align() as usize;
+        let mut content = Vec::with_capacity(8 + size_bytes);
+        content.extend_from_slice(b"data");
+        content.write_u32::<LittleEndian>(size_bytes as u32)?;
+
+        for each_sample in &samples {
+            if each_sample.len() != fmt.num_channels as usize {
+                return Err(WaveReaderError::FormatMismatchError);
+            }
+
+            for &each_channel in each_sample {
+                if each_channel < min || each_channel >= max {
+                    return Err(WaveReaderError::SampleRangeError);
+                }
+
+                let justified = each_channel << shift;
+                match fmt.sample_format() {
+                    SampleFormat::S16 => content.write_i16::<LittleEndian>(justified as i16)?,
+                    SampleFormat::S24 => content.write_i24::<LittleEndian>(justified as i32)?,
+                    SampleFormat::S32 => content.write_i32::<LittleEndian>(justified as i32)?,
+                    _ => content.push((justified + (1 << 7)) as u8),
+                }
+            }
+        }
+
+        let data_chunk = WaveReader::read_data_chunk(0, &fmt, Cursor::new(content), false)?;
+
+        Ok(PCMWaveInfo {
+            riff_header: RiffChunk {
+                file_size: (36 + size_bytes) as u32,
+                is_big_endian: false,
+            },
+            fmt_header: fmt,
+            data_chunks: vec![data_chunk],
+            cue_points: Vec::new(),
+            instrument: None,
+            ixml: None,
+            axml: None,
+            metadata_chunks: BTreeMap::new(),
+            tags: BTreeMap::new(),
+        })
+    }
+}
 
 impl PCMWaveFormatChunk {
+    /// Get the bit depth of the decoded samples
+    /// 
+    /// This is `bps` except for A-law and u-law files, whose 8-bit samples
+    /// are expanded to 16-bit linear PCM samples when read.
+    pub fn sample_bps(&self) -> u16 {
+        match self.audio_format {
+            6 | 7 => 16,
+            _ => self.bps,
+        }
+    }
+
+    /// Get the range of the decoded sample values as `min..max`
+    fn sample_range(&self) -> (i64, i64) {
+        let half_range = 1i64 << (self.sample_bps() - 1);
+        (-half_range, half_range)
+    }
+
+    /// Get how the samples of this WAV file are stored
+    /// 
+    /// For integer samples, this is decided by the number of bytes each
+    /// sample is stored in, so 20-bit samples are `S24`.
+    pub fn sample_format(&self) -> SampleFormat {
+        match (self.audio_format, self.bytes_per_sample()) {
+            (6, _) => SampleFormat::ALaw,
+            (7, _) => SampleFormat::ULaw,
+            (_, 1) => SampleFormat::U8,
+            (_, 2) => SampleFormat::S16,
+            (_, 3) => SampleFormat::S24,
+            _ => SampleFormat::S32,
+        }
+    }
+
     /// Get or calculate the byte rate of this PCM WAV file
-    fn byte_rate(&self) -> u32 {
-        self.samp_rate * u32::from(self.num_channels) * u32::from(self.bps) / 8
+    /// 
+    /// The byte rate is computed in 64 bits, since a high sample rate with
+    /// many channels can exceed the 32-bit field of the fmt chunk.
+    pub fn byte_rate(&self) -> u64 {
+        u64::from(self.samp_rate) * u64::from(self.block_align())
+    }
+
+    /// Get the number of bytes each sample of a channel is stored in
+    /// 
+    /// Samples with a bit depth that is not a multiple of 8 are stored
+    /// in the next whole number of bytes, e.g. 20-bit samples take up
+    /// 3 bytes.
+    fn bytes_per_sample(&self) -> u16 {
+        self.bps.div_ceil(8)
     }
 
     /// Get or calculate the block alignment of this PCM WAV file
@@ -199,32 +846,64 @@ impl PCMWaveFormatChunk {
     /// The *block alignment* is the size of one *inter-channel* sample
     /// in bytes. An *inter-channel sample* is a sample with all of its
     /// channels collated together.
-    fn block_align(&self) -> u16 {
-        self.num_channels * self.bps / 8
+    pub fn block_align(&self) -> u16 {
+        self.num_channels * self.bytes_per_sample()
+    }
+}
+
+impl <R> SharedReader <R> {
+    /// Create the first handle to `reader`, positioned at its start
+    pub fn new(reader: R) -> Self {
+        SharedReader {
+            reader: Rc::new(RefCell::new(reader)),
+            pos: 0,
+        }
+    }
+}
+
+impl <R> Clone for SharedReader <R> {
+    fn clone(&self) -> Self {
+        SharedReader {
+            reader: Rc::clone(&self.reader),
+            pos: self.pos,
+        }
+    }
+}
+
+impl <R: Read + Seek> Read for SharedReader <R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result <usize> {
+        let mut reader = self.reader.borrow_mut();
+        reader.seek(SeekFrom::Start(self.pos))?;
+        let num_read = reader.read(buf)?;
+        self.pos += num_read as u64;
+        Ok(num_read)
+    }
+}
+
+impl <R: Read + Seek> Seek for SharedReader <R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result <u64> {
+        self.pos = match pos {
+            SeekFrom::Start(offset) => offset,
+            SeekFrom::End(_) => self.reader.borrow_mut().seek(pos)?,
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "invalid seek to a negative or overflowing position")
+            })?,
+        };
+        Ok(self.pos)
     }
 }
 
-impl Iterator for PCMWaveDataChunk {
+impl <R: Read + Seek> Iterator for PCMWaveDataChunk <R> {
     type Item = Vec<i64>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let bytes_per_sample = (self.format.bps / 8) as usize;
-        let total_channels = self.format.num_channels as usize;
-        let mut buffer = vec![0u8; self.format.block_align() as usize];
-
-        self.data_buf.read_exact(&mut buffer).ok().map(|_| {
-            buffer.chunks_exact(bytes_per_sample).map(|sample_bytes| {
-                match total_channels {
-                    1 => sample_bytes[0] as i64,
-                    2 => LittleEndian::read_i16(sample_bytes) as i64,
-                    _ => sample_bytes[0] as i64,  
-                }
-            }).collect()
-        })
+        self.try_next().ok().flatten()
     }
 }
 
-impl Iterator for PCMWaveDataChunkWindow {
+impl <R: Read + Seek> FusedIterator for PCMWaveDataChunk <R> {}
+
+impl <R: Read + Seek> Iterator for PCMWaveDataChunkWindow <R> {
     type Item = Vec<Vec<i64>>;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -242,69 +921,514 @@ impl Iterator for PCMWaveDataChunkWindow {
     }
 }
 
-impl PCMWaveDataChunk {
-    pub fn chunks_byte_rate(self) -> PCMWaveDataChunkWindow { 
-        
-        PCMWaveDataChunkWindow {
-            chunk_size: self.format.byte_rate() as usize,
-            data_chunk: self, 
+// Fused since the data chunk it reads from is fused
+impl <R: Read + Seek> FusedIterator for PCMWaveDataChunkWindow <R> {}
+
+impl <R: Read + Seek> Iterator for PCMWaveDataChunkOverlapWindow <R> {
+    type Item = Vec<Vec<i64>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // The window is kept as interleaved samples that never grow past
+        // `size` inter-channel samples, so the buffer is allocated once
+        let num_channels = usize::from(self.data_chunk.format.num_channels);
+        let num_samples = if self.window.is_empty() {
+            self.size
+        } else {
+            let num_dropped = (self.hop * num_channels).min(self.window.len());
+            self.window.drain(..num_dropped);
+            self.hop
+        };
+
+        let mut num_read = 0;
+        for samples in self.data_chunk.by_ref().take(num_samples) {
+            self.window.extend(samples);
+            num_read += 1;
         }
-    }
 
-    pub fn chunks(self, chunk_size: usize) -> PCMWaveDataChunkWindow { 
-        // samp_rate
-        PCMWaveDataChunkWindow {
-            chunk_size: chunk_size,
-            data_chunk: self, 
+        if num_read == 0 {
+            self.window.clear();
+            return None;
         }
+        Some(self.window.make_contiguous().chunks_exact(num_channels).map(<[i64]>::to_vec).collect())
     }
 }
-// TODO: Add more tests here!
-#[cfg(test)]
-mod tests {
-    use super::*;
 
-    #[cfg(test)]
-    mod read_riff {
-        use super::*;
-        use std::io::Write;
+// Fused since a window is only yielded after reading new samples
+impl <R: Read + Seek> FusedIterator for PCMWaveDataChunkOverlapWindow <R> {}
 
-        fn create_temp_file(file_name: &str, content: &[u8]) -> Result <(), io::Error> {
-            let mut file = File::create(file_name)?;
-            file.write_all(content)?;
+impl <R: Read + Seek> PCMWaveDataChunk <R> {
+    /// Read the next inter-channel sample of this data chunk
+    /// 
+    /// This is the fallible counterpart of `next()`. `None` is returned
+    /// once all of the `size_bytes` of the data chunk have been read, and
+    /// for every call after that or after an error, without reading from
+    /// `data_buf` again.
+    /// 
+    /// # Errors
+    /// `TruncatedFrameError` is returned if the file ends before a whole
+    /// inter-channel sample could be read for the declared number of
+    /// channels, and `ReadError` for any other read error.
+    pub fn try_next(&mut self) -> Result <Option <Vec <i64>>, WaveReaderError> {
+        let block_align = self.format.block_align();
+        if self.exhausted || u64::from(self.bytes_read) + u64::from(block_align) > u64::from(self.size_bytes) {
+            self.exhausted = true;
+            return Ok(None);
+        }
 
-            Ok(())
+        let mut buffer = vec![0u8; block_align as usize];
+
+        if let Err(e) = self.data_buf.read_exact(&mut buffer) {
+            self.exhausted = true;
+            return match e.kind() {
+                io::ErrorKind::UnexpectedEof => Err(WaveReaderError::TruncatedFrameError),
+                _ => Err(e.into()),
+            };
         }
-        
-        macro_rules! internal_tests {
-            ($($name:ident: $value:expr,)*) => {
-            $(
-                #[test]
-                fn $name() -> Result <(), WaveReaderError> {
-                    let (input, (will_panic, expected)) = $value;
+        self.bytes_read += u32::from(block_align);
 
-                    let file_name = format!("midp_{}.wav.part", stringify!($name));
-                    let result;
-                    {
-                        create_temp_file(&file_name, input)?;
-                        let mut input_fh = File::open(&file_name)?;
-                        result = WaveReader::read_riff_chunk(&mut input_fh);
-                    }
-                    std::fs::remove_file(&file_name)?;
+        Ok(Some(self.decode_block(&buffer)))
+    }
 
-                    if will_panic {
-                        assert!(result.is_err());
-                    }
-                    else if let Ok(safe_result) = result {
-                        assert_eq!(expected.file_size, safe_result.file_size);
-                        assert_eq!(expected.is_big_endian, safe_result.is_big_endian);
-                    }
-                    else {
-                        result?;
-                    }
+    /// Decode the bytes of one inter-channel sample
+    fn decode_block(&self, buffer: &[u8]) -> Vec <i64> {
+        let bytes_per_sample = self.format.bytes_per_sample() as usize;
+        let samples = buffer.chunks_exact(bytes_per_sample);
 
-                    Ok(())
-                }
+        // Samples narrower than their container are left-justified, so
+        // e.g. 20-bit samples are sign-extended by shifting out the 4 low bits
+        let shift = 8 * bytes_per_sample as u32 - u32::from(self.format.bps);
+
+        // Branch on the format once per block instead of once per sample
+        match self.format.sample_format() {
+            SampleFormat::ALaw => samples.map(|sample_bytes| alaw_to_linear(sample_bytes[0])).collect(),
+            SampleFormat::ULaw => samples.map(|sample_bytes| ulaw_to_linear(sample_bytes[0])).collect(),
+            SampleFormat::U8 => {
+                // 8-bit samples are unsigned and centered at 128
+                let bias = 1i64 << (self.format.bps - 1);
+                samples.map(|sample_bytes| i64::from(sample_bytes[0] >> shift) - bias).collect()
+            },
+            sample_format if self.is_big_endian => decode_signed::<BigEndian>(samples, sample_format, shift),
+            sample_format => decode_signed::<LittleEndian>(samples, sample_format, shift),
+        }
+    }
+
+    /// Iterate over the inter-channel samples from the last to the first
+    /// 
+    /// This seeks back by one inter-channel sample before every read, so
+    /// it is much slower than reading forward. All samples of the data
+    /// chunk are returned, however many were already read forward. If the
+    /// file is truncated, iteration starts from the last whole sample in
+    /// the file. Iteration stops at the first sample or at a read error.
+    pub fn reversed(mut self) -> impl Iterator <Item = Vec <i64>> {
+        let block_align = u64::from(self.format.block_align());
+        let data_end = self.data_start + u64::from(self.size_bytes);
+        let mut pos = match self.data_buf.seek(SeekFrom::End(0)) {
+            Ok(file_len) => {
+                let end = data_end.min(file_len.max(self.data_start));
+                end - (end - self.data_start) % block_align
+            },
+            Err(_) => self.data_start,
+        };
+        let mut buffer = vec![0u8; block_align as usize];
+
+        std::iter::from_fn(move || {
+            if pos < self.data_start + block_align {
+                return None;
+            }
+            pos -= block_align;
+
+            self.data_buf.seek(SeekFrom::Start(pos)).ok()?;
+            self.data_buf.read_exact(&mut buffer).ok()?;
+            Some(self.decode_block(&buffer))
+        })
+    }
+
+    /// Check whether the file holds all `size_bytes` of this data chunk
+    /// 
+    /// This compares the end of the data chunk against the length of the
+    /// file, so a truncated file is found before reading the samples. The
+    /// read position is restored afterwards, so this can be called at any
+    /// point while iterating. It takes `&mut self` only to seek.
+    pub fn is_complete(&mut self) -> io::Result <bool> {
+        let current_pos = self.data_buf.stream_position()?;
+        let file_len = self.data_buf.seek(SeekFrom::End(0))?;
+        self.data_buf.seek(SeekFrom::Start(current_pos))?;
+
+        Ok(self.data_start + u64::from(self.size_bytes) <= file_len)
+    }
+
+    /// Get the duration of this data chunk in seconds
+    /// 
+    /// This is computed from `size_bytes` and the byte rate of the format,
+    /// so nothing is read from the file. A format with a sample rate of 0
+    /// has no duration, so 0.0 is returned for it.
+    pub fn duration_secs(&self) -> f64 {
+        match self.format.byte_rate() {
+            0 => 0.0,
+            byte_rate => f64::from(self.size_bytes) / byte_rate as f64,
+        }
+    }
+
+    /// Get the number of inter-channel samples in this data chunk
+    /// 
+    /// Trailing bytes that do not fill a whole inter-channel sample are
+    /// not counted.
+    pub fn num_frames(&self) -> u32 {
+        self.size_bytes / u32::from(self.format.block_align())
+    }
+
+    /// Get the DC offset of each channel of this data chunk
+    /// 
+    /// The DC offset of a channel is the mean of its samples. Subtracting
+    /// it from a biased recording can improve its compression. A data chunk
+    /// with no samples has a DC offset of 0.0 for every channel.
+    pub fn dc_offset(self) -> Vec <f64> {
+        let total_channels = self.format.num_channels as usize;
+        let mut sums = vec![0i64; total_channels];
+        let mut num_samples: u64 = 0;
+
+        for each_sample in self {
+            for (sum, each_channel) in sums.iter_mut().zip(each_sample) {
+                *sum += each_channel;
+            }
+            num_samples += 1;
+        }
+
+        sums.iter()
+            .map(|sum| if num_samples == 0 { 0.0 } else { *sum as f64 / num_samples as f64 })
+            .collect()
+    }
+
+    /// Get the peak of each channel of this data chunk
+    /// 
+    /// The peak of a channel is its largest absolute sample value. With
+    /// the `parallel` feature, the channels are analyzed in parallel.
+    pub fn peaks(self) -> Vec <i64> {
+        let channels = self.split_channels();
+
+        #[cfg(feature = "parallel")]
+        let channel_iter = channels.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let channel_iter = channels.iter();
+
+        channel_iter
+            .map(|channel| channel.iter().map(|sample| sample.abs()).max().unwrap_or(0))
+            .collect()
+    }
+
+    /// Get the RMS level of each channel of this data chunk
+    /// 
+    /// The RMS level of a channel is the square root of the mean of its
+    /// squared samples. With the `parallel` feature, the channels are
+    /// analyzed in parallel.
+    pub fn rms(self) -> Vec <f64> {
+        let channels = self.split_channels();
+
+        #[cfg(feature = "parallel")]
+        let channel_iter = channels.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let channel_iter = channels.iter();
+
+        channel_iter
+            .map(|channel| {
+                if channel.is_empty() {
+                    return 0.0;
+                }
+                let sum_squares: f64 = channel.iter().map(|sample| (*sample as f64).powi(2)).sum();
+                (sum_squares / channel.len() as f64).sqrt()
+            })
+            .collect()
+    }
+
+    /// Read the whole data chunk into one vector of samples per channel
+    fn split_channels(self) -> Vec <Vec <i64>> {
+        let total_channels = self.format.num_channels as usize;
+        let mut channels: Vec <Vec <i64>> = vec![Vec::new(); total_channels];
+
+        for each_sample in self {
+            for (channel, each_channel) in channels.iter_mut().zip(each_sample) {
+                channel.push(each_channel);
+            }
+        }
+
+        channels
+    }
+
+    /// Get a histogram of the sample values of this data chunk
+    /// 
+    /// The whole range of sample values for the bit depth is split into
+    /// `bins` bins of equal width, and the samples of all channels are
+    /// counted in them. The first bin starts at the smallest sample value,
+    /// so for 16-bit samples and 4 bins, the first bin counts the samples
+    /// from -32768 to -16385.
+    /// 
+    /// # Panics
+    /// Panics if `bins` is 0.
+    pub fn histogram(self, bins: usize) -> Vec <u64> {
+        assert!(bins > 0, "a histogram needs at least 1 bin");
+
+        let (min, max) = self.format.sample_range();
+        let range = i128::from(max - min);
+        let mut histogram = vec![0u64; bins];
+
+        for each_sample in self {
+            for each_channel in each_sample {
+                let offset = i128::from(each_channel - min).clamp(0, range - 1);
+                histogram[(offset * bins as i128 / range) as usize] += 1;
+            }
+        }
+
+        histogram
+    }
+
+    /// Get the true peak of this data chunk
+    /// 
+    /// The true peak is the peak of the continuous signal the samples
+    /// represent, which can be higher than the largest sample when the
+    /// signal peaks between samples. The signal is upsampled by a factor
+    /// of `oversample` with windowed sinc interpolation and the largest
+    /// absolute value is returned, normalized so that 1.0 is full scale.
+    pub fn true_peak(self, oversample: usize) -> f64 {
+        let full_scale = (1i64 << (self.format.sample_bps() - 1)) as f64;
+        let channels: Vec <Vec <f64>> = self.split_channels().into_iter()
+            .map(|channel| channel.into_iter().map(|sample| sample as f64).collect())
+            .collect();
+
+        let mut peak: f64 = 0.0;
+        for channel in &channels {
+            for (i, each_sample) in channel.iter().enumerate() {
+                peak = peak.max(each_sample.abs());
+
+                for phase in 1..oversample {
+                    let t = i as f64 + phase as f64 / oversample as f64;
+                    peak = peak.max(sinc_interpolate(channel, t).abs());
+                }
+            }
+        }
+
+        peak / full_scale
+    }
+
+    pub fn chunks_byte_rate(self) -> PCMWaveDataChunkWindow <R> { 
+        
+        PCMWaveDataChunkWindow {
+            chunk_size: self.format.byte_rate() as usize,
+            data_chunk: self, 
+        }
+    }
+
+    pub fn chunks(self, chunk_size: usize) -> PCMWaveDataChunkWindow <R> { 
+        // samp_rate
+        PCMWaveDataChunkWindow {
+            chunk_size,
+            data_chunk: self, 
+        }
+    }
+
+    /// Iterate over overlapping windows of `size` inter-channel samples
+    /// 
+    /// Each window starts `hop` samples after the previous one, so a `hop`
+    /// of half the `size` gives windows with 50% overlap. The overlapping
+    /// samples are kept in memory and not read again. The last window holds
+    /// whatever samples are left and may be shorter than `size`.
+    /// 
+    /// # Panics
+    /// Panics if `hop` is 0 or greater than `size`.
+    pub fn chunks_overlapping(self, size: usize, hop: usize) -> PCMWaveDataChunkOverlapWindow <R> {
+        assert!(hop > 0 && hop <= size, "hop must be within 1 and the window size");
+
+        PCMWaveDataChunkOverlapWindow {
+            size,
+            hop,
+            window: VecDeque::with_capacity(size * usize::from(self.format.num_channels)),
+            data_chunk: self,
+        }
+    }
+}
+/// Get the value of a sampled signal at the fractional position `t`
+/// 
+/// This uses a Hann-windowed sinc spanning `SINC_TAPS` samples on each
+/// side of `t`, treating samples outside of `samples` as silence.
+fn sinc_interpolate(samples: &[f64], t: f64) -> f64 {
+    const SINC_TAPS: isize = 16;
+
+    let center = t.floor() as isize;
+    let mut value = 0.0;
+
+    for k in (center - SINC_TAPS + 1)..=(center + SINC_TAPS) {
+        if k < 0 || k as usize >= samples.len() {
+            continue;
+        }
+
+        let x = t - k as f64;
+        let window = 0.5 * (1.0 + (std::f64::consts::PI * x / SINC_TAPS as f64).cos());
+        let sinc = if x == 0.0 { 1.0 } else { (std::f64::consts::PI * x).sin() / (std::f64::consts::PI * x) };
+        value += samples[k as usize] * sinc * window;
+    }
+
+    value
+}
+
+/// Decode signed integer samples stored in the byte order `E`
+/// 
+/// Each sample is shifted right by `shift` bits to undo the left
+/// justification of samples narrower than their container.
+fn decode_signed<E: ByteOrder>(samples: std::slice::ChunksExact <u8>, sample_format: SampleFormat, shift: u32) -> Vec <i64> {
+    match sample_format {
+        SampleFormat::S16 => samples.map(|sample_bytes| i64::from(E::read_i16(sample_bytes) >> shift)).collect(),
+        SampleFormat::S24 => samples.map(|sample_bytes| i64::from(E::read_i24(sample_bytes) >> shift)).collect(),
+        _ => samples.map(|sample_bytes| i64::from(E::read_i32(sample_bytes) >> shift)).collect(),
+    }
+}
+
+/// Expand an A-law (G.711) byte into a 16-bit linear PCM sample
+fn alaw_to_linear(alaw: u8) -> i64 {
+    let alaw = alaw ^ 0x55; // Even bits are inverted
+    let exponent = (alaw >> 4) & 0x07;
+    let mantissa = i64::from(alaw & 0x0F) << 4;
+
+    let magnitude = match exponent {
+        0 => mantissa + 0x8,
+        _ => (mantissa + 0x108) << (exponent - 1),
+    };
+
+    if alaw & 0x80 != 0 { magnitude } else { -magnitude }
+}
+
+/// Expand a u-law (G.711) byte into a 16-bit linear PCM sample
+fn ulaw_to_linear(ulaw: u8) -> i64 {
+    let ulaw = !ulaw; // All bits are inverted
+    let exponent = (ulaw >> 4) & 0x07;
+    let mantissa = i64::from(ulaw & 0x0F);
+
+    let magnitude = (((mantissa << 3) + 0x84) << exponent) - 0x84;
+
+    if ulaw & 0x80 != 0 { -magnitude } else { magnitude }
+}
+
+/// Get the tags of the `id3 ` chunk of a WAV file
+/// 
+/// Both chunk ids `id3 ` and `ID3 ` are used in the wild. An empty map is
+/// returned if the file has neither.
+fn read_id3_tags(metadata_chunks: &BTreeMap <[u8; 4], Vec <Vec <u8>>>) -> BTreeMap <String, String> {
+    metadata_chunks.get(b"id3 ")
+        .or_else(|| metadata_chunks.get(b"ID3 "))
+        .and_then(|tags| tags.first())
+        .map(|tag| parse_id3_tags(tag))
+        .unwrap_or_default()
+}
+
+/// Parse the text frames of an ID3v2.3 or ID3v2.4 tag
+/// 
+/// The frames are keyed by their frame id, so the title, artist and album
+/// are under `TIT2`, `TPE1` and `TALB`. Only text frames are read, except
+/// the user-defined `TXXX` frame. Parsing stops at the padding or at the
+/// first malformed frame, keeping the frames read so far. Tags of other
+/// versions and unsynchronised tags give an empty map.
+fn parse_id3_tags(tag: &[u8]) -> BTreeMap <String, String> {
+    // Syncsafe integers keep the MSB of each byte clear
+    fn syncsafe(bytes: &[u8]) -> usize {
+        bytes.iter().fold(0, |value, byte| (value << 7) | usize::from(byte & 0x7F))
+    }
+
+    let mut tags = BTreeMap::new();
+    if tag.len() < 10 || &tag[0..3] != b"ID3" {
+        return tags;
+    }
+
+    let version = tag[3];
+    let flags = tag[5];
+    if !(version == 3 || version == 4) || flags & 0x80 != 0 {
+        return tags;
+    }
+
+    let tag_end = (10 + syncsafe(&tag[6..10])).min(tag.len());
+    let mut pos = 10;
+
+    if flags & 0x40 != 0 && tag_end >= 14 { // Skip the extended header
+        pos += match version {
+            3 => 4 + BigEndian::read_u32(&tag[10..14]) as usize,
+            _ => syncsafe(&tag[10..14]),
+        };
+    }
+
+    while pos + 10 <= tag_end && tag[pos] != 0 {
+        let frame_id = &tag[pos..pos + 4];
+        let frame_size = match version {
+            3 => BigEndian::read_u32(&tag[pos + 4..pos + 8]) as usize,
+            _ => syncsafe(&tag[pos + 4..pos + 8]),
+        };
+        let body_start = pos + 10;
+        if frame_size == 0 || body_start + frame_size > tag_end {
+            break;
+        }
+
+        if frame_id[0] == b'T' && frame_id != b"TXXX" {
+            let text = decode_id3_text(tag[body_start], &tag[body_start + 1..body_start + frame_size]);
+            tags.insert(String::from_utf8_lossy(frame_id).into_owned(), text);
+        }
+
+        pos = body_start + frame_size;
+    }
+
+    tags
+}
+
+/// Decode the text of an ID3v2 text frame with the given encoding byte
+/// 
+/// The encodings are ISO-8859-1 (0), UTF-16 with a byte order mark (1),
+/// UTF-16BE (2) and UTF-8 (3). Trailing NUL terminators are removed.
+fn decode_id3_text(encoding: u8, text: &[u8]) -> String {
+    let decoded = match encoding {
+        0 => text.iter().map(|byte| char::from(*byte)).collect(),
+        1 | 2 => {
+            let (is_little_endian, text) = match text {
+                [0xFF, 0xFE, rest @ ..] => (true, rest),
+                [0xFE, 0xFF, rest @ ..] => (false, rest),
+                _ => (false, text),
+            };
+            let units: Vec <u16> = text.chunks_exact(2)
+                .map(|unit| if is_little_endian { LittleEndian::read_u16(unit) } else { BigEndian::read_u16(unit) })
+                .collect();
+            String::from_utf16_lossy(&units)
+        },
+        _ => String::from_utf8_lossy(text).into_owned(),
+    };
+
+    decoded.trim_end_matches('\0').to_string()
+}
+
+// TODO: Add more tests here!
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(test)]
+    mod read_riff {
+        use super::*;
+        
+        macro_rules! internal_tests {
+            ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() -> Result <(), WaveReaderError> {
+                    let (input, (will_panic, expected)) = $value;
+
+                    let result = WaveReader::read_riff_chunk(&mut Cursor::new(input));
+
+                    if will_panic {
+                        assert!(result.is_err());
+                    }
+                    else if let Ok(safe_result) = result {
+                        assert_eq!(expected.file_size, safe_result.file_size);
+                        assert_eq!(expected.is_big_endian, safe_result.is_big_endian);
+                    }
+                    else {
+                        result?;
+                    }
+
+                    Ok(())
+                }
             )*
             }
         }
@@ -388,14 +1512,95 @@ mod tests {
     #[cfg(test)]
     mod read_wav_fmt {
         use super::*;
-        use std::io::Write;
 
-        fn create_temp_file(file_name: &str, content: &[u8]) -> Result <(), io::Error> {
-            let mut file = File::create(file_name)?;
-            file.write_all(content)?;
+        #[test]
+        fn it_unsupported_format() {
+            // u-law is only supported with 8-bit samples
+            let mut cursor = Cursor::new([
+                0x66, 0x6d, 0x74, 0x20,
+                0x10, 0x0, 0x0, 0x0,
+                0x07, 0x0,
+                0x01, 0x0,
+                0x40, 0x1f, 0x0, 0x0,
+                0x80, 0x3e, 0x0, 0x0,
+                0x02, 0x00, 0x10, 0x0,
+            ]);
+            let result = WaveReader::read_fmt_chunk(&mut cursor, false);
+
+            assert!(matches!(result, Err(WaveReaderError::UnsupportedFormat(7))));
+        }
+
+        #[test]
+        fn it_unsupported_bit_depth() {
+            for bps in [0u8, 33, 64] {
+                let mut cursor = Cursor::new([
+                    0x66, 0x6d, 0x74, 0x20,
+                    0x10, 0x0, 0x0, 0x0,
+                    0x01, 0x0,
+                    0x01, 0x0,
+                    0x40, 0x1f, 0x0, 0x0,
+                    0x00, 0x7d, 0x0, 0x0,
+                    0x04, 0x00, bps, 0x0,
+                ]);
+                let result = WaveReader::read_fmt_chunk(&mut cursor, false);
+
+                assert!(matches!(result, Err(WaveReaderError::UnsupportedFormat(1))));
+            }
+        }
+
+        #[test]
+        fn it_zero_sample_rate() {
+            let mut cursor = Cursor::new([
+                0x66, 0x6d, 0x74, 0x20,
+                0x10, 0x0, 0x0, 0x0,
+                0x01, 0x0,
+                0x01, 0x0,
+                0x0, 0x0, 0x0, 0x0,
+                0x0, 0x0, 0x0, 0x0,
+                0x02, 0x0, 0x10, 0x0,
+            ]);
+            let result = WaveReader::read_fmt_chunk(&mut cursor, false);
+
+            assert!(matches!(result, Err(WaveReaderError::SampleRateError)));
+        }
+
+        #[test]
+        fn it_companded() -> Result <(), WaveReaderError> {
+            let mut cursor = Cursor::new([
+                0x66, 0x6d, 0x74, 0x20,
+                0x12, 0x0, 0x0, 0x0,
+                0x06, 0x0,
+                0x01, 0x0,
+                0x40, 0x1f, 0x0, 0x0,
+                0x40, 0x1f, 0x0, 0x0,
+                0x01, 0x00, 0x08, 0x0,
+                0x0, 0x0,
+            ]);
+            let result = WaveReader::read_fmt_chunk(&mut cursor, false)?;
+
+            assert_eq!(result.audio_format, 6);
+            assert_eq!(result.bps, 8);
+            assert_eq!(result.sample_bps(), 16);
+            assert_eq!(result.block_align(), 1);
 
             Ok(())
         }
+
+        #[test]
+        fn it_not_pcm() {
+            let mut cursor = Cursor::new([
+                0x66, 0x6d, 0x74, 0x20,
+                0x10, 0x0, 0x0, 0x0,
+                0x0, 0x0,
+                0x01, 0x0,
+                0x40, 0x1f, 0x0, 0x0,
+                0x40, 0x1f, 0x0, 0x0,
+                0x01, 0x00, 0x08, 0x0,
+            ]);
+            let result = WaveReader::read_fmt_chunk(&mut cursor, false);
+
+            assert!(matches!(result, Err(WaveReaderError::NotPCMError)));
+        }
         
         macro_rules! internal_tests {
             ($($name:ident: $value:expr,)*) => {
@@ -404,14 +1609,7 @@ mod tests {
                 fn $name() -> Result <(), WaveReaderError> {
                     let (input, (will_panic, expected)) = $value;
 
-                    let file_name = format!("midp_{}.wav.part", stringify!($name));
-                    let result;
-                    {
-                        create_temp_file(&file_name, input)?;
-                        let mut input_fh = File::open(&file_name)?;
-                        result = WaveReader::read_fmt_chunk(&mut input_fh);
-                    }
-                    std::fs::remove_file(&file_name)?;
+                    let result = WaveReader::read_fmt_chunk(&mut Cursor::new(input), false);
 
                     if will_panic {
                         assert!(result.is_err());
@@ -445,6 +1643,7 @@ mod tests {
                 (
                     false,
                     PCMWaveFormatChunk {
+                        audio_format: 1,
                         num_channels: 1,
                         samp_rate: 44100,
                         bps: 8,
@@ -463,6 +1662,7 @@ mod tests {
                 (
                     false,
                     PCMWaveFormatChunk {
+                        audio_format: 1,
                         num_channels: 2,
                         samp_rate: 44100,
                         bps: 8,
@@ -481,6 +1681,7 @@ mod tests {
                 (
                     false,
                     PCMWaveFormatChunk {
+                        audio_format: 1,
                         num_channels: 2,
                         samp_rate: 44100,
                         bps: 16,
@@ -489,7 +1690,1110 @@ mod tests {
         }
     }
 
+    #[cfg(test)]
     mod read_data_fmt {
-        // TODO
+        use super::*;
+
+        const MONO_8: PCMWaveFormatChunk = PCMWaveFormatChunk {
+            audio_format: 1,
+            num_channels: 1,
+            samp_rate: 8000,
+            bps: 8,
+        };
+
+        macro_rules! internal_tests {
+            ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() -> Result <(), WaveReaderError> {
+                    let (input, (will_panic, expected)) = $value;
+
+                    let result = WaveReader::read_data_chunk(0, &MONO_8, Cursor::new(input), false);
+
+                    if will_panic {
+                        assert!(result.is_err());
+                    }
+                    else if let Ok(safe_result) = result {
+                        assert_eq!(expected, safe_result.size_bytes);
+                        assert_eq!(8, safe_result.data_start);
+                    }
+                    else {
+                        result?;
+                    }
+
+                    Ok(())
+                }
+            )*
+            }
+        }
+
+        internal_tests! {
+            it_valid_00: (
+                &[0x64, 0x61, 0x74, 0x61, 0x04, 0x0, 0x0, 0x0, 0x80, 0x80, 0x80, 0x80],
+                (false, 4),
+            ),
+            it_valid_streaming: (
+                &[&[0x64, 0x61, 0x74, 0x61, 0xff, 0xff, 0xff, 0xff][..], &[0x80; 200][..]].concat(),
+                (false, 200),
+            ),
+            it_bad_data: (
+                &[0x66, 0x61, 0x63, 0x74, 0x04, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0],
+                (true, 0),
+            ),
+        }
+
+        const SURROUND_16: PCMWaveFormatChunk = PCMWaveFormatChunk {
+            audio_format: 1,
+            num_channels: 6,
+            samp_rate: 44100,
+            bps: 16,
+        };
+
+        #[test]
+        fn it_20_bit() -> Result <(), WaveReaderError> {
+            let mono_20 = PCMWaveFormatChunk {
+                audio_format: 1,
+                num_channels: 1,
+                samp_rate: 96000,
+                bps: 20,
+            };
+            let input = Cursor::new(&[
+                0x64, 0x61, 0x74, 0x61, 0x0f, 0x0, 0x0, 0x0,
+                0x10, 0x00, 0x00,
+                0xf0, 0xff, 0xff,
+                0xf0, 0xff, 0x7f,
+                0x00, 0x00, 0x80,
+                0x00, 0x00, 0x00,
+                0x00,
+            ]);
+            let result = WaveReader::read_data_chunk(0, &mono_20, input, false);
+
+            let samples: Vec <i64> = result?.flatten().collect();
+            assert_eq!(samples, vec![1, -1, 0x7FFFF, -0x80000, 0]);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_alaw() -> Result <(), WaveReaderError> {
+            let mono_alaw = PCMWaveFormatChunk {
+                audio_format: 6,
+                ..MONO_8
+            };
+            let mut cursor = Cursor::new([0x64, 0x61, 0x74, 0x61, 0x04, 0x0, 0x0, 0x0, 0xd5, 0x55, 0xaa, 0x2a]);
+            let samples: Vec <i64> = WaveReader::read_data_chunk(0, &mono_alaw, &mut cursor, false)?.flatten().collect();
+
+            assert_eq!(samples, vec![8, -8, 32256, -32256]);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_ulaw() -> Result <(), WaveReaderError> {
+            let mono_ulaw = PCMWaveFormatChunk {
+                audio_format: 7,
+                ..MONO_8
+            };
+            let mut cursor = Cursor::new([0x64, 0x61, 0x74, 0x61, 0x05, 0x0, 0x0, 0x0, 0xff, 0x7f, 0x80, 0x00, 0xfe]);
+            let samples: Vec <i64> = WaveReader::read_data_chunk(0, &mono_ulaw, &mut cursor, false)?.flatten().collect();
+
+            assert_eq!(samples, vec![0, 0, 32124, -32124, 8]);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_true_peak() -> Result <(), WaveReaderError> {
+            // A quarter-rate sine with a 45 degree phase peaks exactly between samples
+            let mono_16 = PCMWaveFormatChunk {
+                bps: 16,
+                ..MONO_8
+            };
+            let samples: Vec <i16> = (0..512)
+                .map(|n| (20000.0 * (std::f64::consts::FRAC_PI_2 * n as f64 + std::f64::consts::FRAC_PI_4).sin()).round() as i16)
+                .collect();
+            let mut content = vec![0x64, 0x61, 0x74, 0x61, 0x0, 0x04, 0x0, 0x0];
+            for each_sample in &samples {
+                content.extend_from_slice(&each_sample.to_le_bytes());
+            }
+
+            let sample_peak = WaveReader::read_data_chunk(0, &mono_16, Cursor::new(&content), false)?.true_peak(1);
+            let true_peak = WaveReader::read_data_chunk(0, &mono_16, Cursor::new(&content), false)?.true_peak(4);
+
+            assert_eq!(sample_peak, 14142.0 / 32768.0);
+            assert!(true_peak > sample_peak);
+            assert!((true_peak - 20000.0 / 32768.0).abs() < 0.02);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_sample_widths() -> Result <(), WaveReaderError> {
+            let cases: [(u16, SampleFormat, &[u8], Vec <i64>); 4] = [
+                (8, SampleFormat::U8, &[0x80, 0x00, 0xff], vec![0, -128, 127]),
+                (16, SampleFormat::S16, &[0x01, 0x80, 0xff, 0x7f], vec![-32767, 32767]),
+                (24, SampleFormat::S24, &[0x00, 0x00, 0x80, 0x56, 0x34, 0x12], vec![-8_388_608, 0x123456]),
+                (32, SampleFormat::S32, &[0x00, 0x00, 0x00, 0x80, 0xfe, 0xff, 0xff, 0xff, 0x78, 0x56, 0x34, 0x12], vec![i64::from(i32::MIN), -2, 0x12345678]),
+            ];
+
+            for (bps, sample_format, sample_bytes, expected) in cases {
+                let fmt_info = PCMWaveFormatChunk { bps, ..MONO_8 };
+                assert_eq!(fmt_info.sample_format(), sample_format);
+
+                let content = [&[0x64, 0x61, 0x74, 0x61][..], &(sample_bytes.len() as u32).to_le_bytes(), sample_bytes].concat();
+                let samples: Vec <i64> = WaveReader::read_data_chunk(0, &fmt_info, Cursor::new(content), false)?.flatten().collect();
+                assert_eq!(samples, expected);
+            }
+
+            assert_eq!(PCMWaveFormatChunk { bps: 20, ..MONO_8 }.sample_format(), SampleFormat::S24);
+            assert_eq!(PCMWaveFormatChunk { audio_format: 6, ..MONO_8 }.sample_format(), SampleFormat::ALaw);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_is_complete() -> Result <(), WaveReaderError> {
+            let content = [
+                0x64, 0x61, 0x74, 0x61, 0x08, 0x0, 0x0, 0x0,
+                0x01, 0x0, 0x02, 0x0, 0x03, 0x0, 0x04, 0x0,
+            ];
+            let mono_16 = PCMWaveFormatChunk { num_channels: 1, ..SURROUND_16 };
+
+            let mut complete = WaveReader::read_data_chunk(0, &mono_16, Cursor::new(&content), false)?;
+            assert_eq!(complete.next(), Some(vec![1]));
+            assert!(complete.is_complete()?);
+            assert_eq!(complete.next(), Some(vec![2]));
+
+            let mut truncated = WaveReader::read_data_chunk(0, &mono_16, Cursor::new(&content[..12]), false)?;
+            assert!(!truncated.is_complete()?);
+            assert_eq!(truncated.next(), Some(vec![1]));
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_peaks_rms() -> Result <(), WaveReaderError> {
+            let quad_16 = PCMWaveFormatChunk {
+                num_channels: 4,
+                ..SURROUND_16
+            };
+            let channels: [[i16; 4]; 4] = [
+                [100, -200, 300, -400],
+                [0, 0, 0, 0],
+                [-32768, 32767, 5, 5],
+                [3, -4, 3, -4],
+            ];
+            let mut content = vec![0x64, 0x61, 0x74, 0x61, 0x20, 0x0, 0x0, 0x0];
+            for i in 0..4 {
+                for channel in &channels {
+                    content.extend_from_slice(&channel[i].to_le_bytes());
+                }
+            }
+
+            let peaks = WaveReader::read_data_chunk(0, &quad_16, Cursor::new(&content), false)?.peaks();
+            let rms = WaveReader::read_data_chunk(0, &quad_16, Cursor::new(&content), false)?.rms();
+
+            // Serial reference computed independently of the reader
+            let expected_peaks: Vec <i64> = channels.iter()
+                .map(|channel| channel.iter().map(|sample| i64::from(*sample).abs()).max().unwrap())
+                .collect();
+            assert_eq!(peaks, expected_peaks);
+            assert_eq!(peaks, vec![400, 0, 32768, 4]);
+            assert!((rms[0] - 75000.0f64.sqrt()).abs() < 1e-9);
+            assert_eq!(rms[1], 0.0);
+            assert!((rms[3] - 3.5355339059327378).abs() < 1e-9);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_bad_alignment() -> Result <(), WaveReaderError> {
+            // 5 stereo samples do not fill a whole number of 6-channel samples
+            let input = Cursor::new([&[0x64, 0x61, 0x74, 0x61, 0x14, 0x0, 0x0, 0x0][..], &[0x01; 20][..]].concat());
+            let result = WaveReader::read_data_chunk(0, &SURROUND_16, input, false);
+
+            assert!(matches!(result, Err(WaveReaderError::DataAlignmentError)));
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_truncated_frame() -> Result <(), WaveReaderError> {
+            // The size declares 2 6-channel samples but only 5 stereo samples follow
+            let input = Cursor::new([&[0x64, 0x61, 0x74, 0x61, 0x18, 0x0, 0x0, 0x0][..], &[0x01; 20][..]].concat());
+            let result = WaveReader::read_data_chunk(0, &SURROUND_16, input, false);
+
+            let mut data_chunk = result?;
+            assert_eq!(data_chunk.try_next()?.map(|samples| samples.len()), Some(6));
+            assert!(matches!(data_chunk.try_next(), Err(WaveReaderError::TruncatedFrameError)));
+
+            let end_pos = data_chunk.data_buf.stream_position()?;
+            assert_eq!(data_chunk.next(), None);
+            assert_eq!(data_chunk.next(), None);
+            assert_eq!(data_chunk.data_buf.stream_position()?, end_pos);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_dc_offset() -> Result <(), WaveReaderError> {
+            let stereo_16 = PCMWaveFormatChunk {
+                num_channels: 2,
+                ..SURROUND_16
+            };
+            // Left channel: 90, 110, 100, 100; right channel: -5, 5, 10, -10
+            let input = Cursor::new(&[
+                0x64, 0x61, 0x74, 0x61, 0x10, 0x0, 0x0, 0x0,
+                0x5a, 0x0, 0xfb, 0xff,
+                0x6e, 0x0, 0x05, 0x0,
+                0x64, 0x0, 0x0a, 0x0,
+                0x64, 0x0, 0xf6, 0xff,
+            ]);
+            let result = WaveReader::read_data_chunk(0, &stereo_16, input, false);
+
+            let dc_offset = result?.dc_offset();
+            assert_eq!(dc_offset.len(), 2);
+            assert!((dc_offset[0] - 100.0).abs() < 1e-9);
+            assert!(dc_offset[1].abs() < 1e-9);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_fused() -> Result <(), WaveReaderError> {
+            let input = Cursor::new(&[0x64, 0x61, 0x74, 0x61, 0x02, 0x0, 0x0, 0x0, 0x80, 0x81]);
+            let result = WaveReader::read_data_chunk(0, &MONO_8, input, false);
+
+            let mut data_chunk = result?;
+            assert_eq!(data_chunk.by_ref().count(), 2);
+
+            let end_pos = data_chunk.data_buf.stream_position()?;
+            assert_eq!(data_chunk.next(), None);
+            assert_eq!(data_chunk.next(), None);
+            assert_eq!(data_chunk.data_buf.stream_position()?, end_pos);
+
+            let mut window = data_chunk.chunks(4);
+            assert_eq!(window.next(), None);
+            assert_eq!(window.next(), None);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_stops_at_size() -> Result <(), WaveReaderError> {
+            // Bytes after the data chunk are not samples
+            let input = Cursor::new(&[0x64, 0x61, 0x74, 0x61, 0x02, 0x0, 0x0, 0x0, 0x80, 0x81, 0x4c, 0x49, 0x53, 0x54]);
+            let result = WaveReader::read_data_chunk(0, &MONO_8, input, false);
+
+            assert_eq!(result?.count(), 2);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_size_near_u32_max() -> Result <(), WaveReaderError> {
+            let input = Cursor::new(&[0x64, 0x61, 0x74, 0x61, 0xfc, 0xff, 0xff, 0xff, 0x01, 0x0, 0x02, 0x0]);
+            let mut data_chunk = WaveReader::read_data_chunk(0, &SURROUND_16, input, false)?;
+
+            // Pretend all of a 4 GiB data chunk has been read already
+            data_chunk.bytes_read = data_chunk.size_bytes;
+            assert_eq!(data_chunk.try_next()?, None);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_duration() -> Result <(), WaveReaderError> {
+            let stereo_16 = PCMWaveFormatChunk {
+                num_channels: 2,
+                ..SURROUND_16
+            };
+            // One second of 44100 Hz stereo 16-bit audio is 176400 bytes
+            let input = Cursor::new([&[0x64, 0x61, 0x74, 0x61, 0x10, 0xb1, 0x02, 0x0][..], &[0x0; 176400][..]].concat());
+            let data_chunk = WaveReader::read_data_chunk(0, &stereo_16, input, false)?;
+
+            assert_eq!(stereo_16.byte_rate(), 176400);
+            assert_eq!(stereo_16.block_align(), 4);
+            assert_eq!(data_chunk.num_frames(), 44100);
+            assert!((data_chunk.duration_secs() - 1.0).abs() < 1e-9);
+
+            let input = Cursor::new([&[0x64, 0x61, 0x74, 0x61, 0x88, 0x58, 0x01, 0x0][..], &[0x0; 88200][..]].concat());
+            let data_chunk = WaveReader::read_data_chunk(0, &stereo_16, input, false)?;
+
+            assert_eq!(data_chunk.num_frames(), 22050);
+            assert!((data_chunk.duration_secs() - 0.5).abs() < 1e-9);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_byte_rate_over_u32() -> Result <(), WaveReaderError> {
+            let many_channels = PCMWaveFormatChunk {
+                num_channels: 1024,
+                samp_rate: 4_000_000,
+                bps: 32,
+                ..SURROUND_16
+            };
+            assert_eq!(many_channels.byte_rate(), 16_384_000_000);
+
+            let no_rate = PCMWaveFormatChunk { samp_rate: 0, ..SURROUND_16 };
+            let input = Cursor::new([&[0x64, 0x61, 0x74, 0x61, 0x0c, 0x0, 0x0, 0x0][..], &[0x0; 12][..]].concat());
+            let data_chunk = WaveReader::read_data_chunk(0, &no_rate, input, false)?;
+            assert_eq!(data_chunk.duration_secs(), 0.0);
+
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod wav_samples {
+        use super::*;
+
+        fn open_data_chunk(content: &[u8], fmt_info: &PCMWaveFormatChunk) -> Result <PCMWaveDataChunk <Cursor <Vec <u8>>>, WaveReaderError> {
+            WaveReader::read_data_chunk(0, fmt_info, Cursor::new(content.to_vec()), false)
+        }
+
+        fn create_wave_info(fmt_info: PCMWaveFormatChunk, data_chunks: Vec <PCMWaveDataChunk <Cursor <Vec <u8>>>>) -> PCMWaveInfo <Cursor <Vec <u8>>> {
+            PCMWaveInfo {
+                riff_header: RiffChunk {
+                    file_size: 0,
+                    is_big_endian: false,
+                },
+                fmt_header: fmt_info,
+                data_chunks,
+                cue_points: Vec::new(),
+                instrument: None,
+                ixml: None,
+                axml: None,
+                metadata_chunks: BTreeMap::new(),
+                tags: BTreeMap::new(),
+            }
+        }
+
+        const STEREO_16: PCMWaveFormatChunk = PCMWaveFormatChunk {
+            audio_format: 1,
+            num_channels: 2,
+            samp_rate: 44100,
+            bps: 16,
+        };
+
+        #[test]
+        fn it_from_samples() -> Result <(), WaveReaderError> {
+            let sine = crate::testutil::gen_sine(440.0, 44100, 0.1, 16, 2);
+            let wave_info = PCMWaveInfo::from_samples(sine.clone(), STEREO_16)?;
+
+            assert_eq!(wave_info.data_chunks.len(), 1);
+            assert!(wave_info.file_size_matches(44 + sine.len() as u64 * 4));
+            assert_eq!(wave_info.samples()?.collect::<Vec <_>>(), sine);
+
+            let sine_24 = crate::testutil::gen_sine(1000.0, 48000, 0.01, 24, 1);
+            let mono_24 = PCMWaveFormatChunk { num_channels: 1, bps: 24, ..STEREO_16 };
+            let left = PCMWaveInfo::from_samples(sine_24.clone(), mono_24)?.channel_samples(0)?;
+            assert_eq!(left, sine_24.iter().map(|each_sample| each_sample[0]).collect::<Vec <_>>());
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_chunks_overlapping() -> Result <(), WaveReaderError> {
+            let mono_16 = PCMWaveFormatChunk { num_channels: 1, ..STEREO_16 };
+            let samples: Vec <Vec <i64>> = (0..3000).map(|i| vec![i]).collect();
+            let data_chunk = PCMWaveInfo::from_samples(samples, mono_16)?.data_chunks.remove(0);
+
+            let windows: Vec <Vec <Vec <i64>>> = data_chunk.chunks_overlapping(1024, 512).collect();
+            let sizes: Vec <usize> = windows.iter().map(|window| window.len()).collect();
+            assert_eq!(sizes, vec![1024, 1024, 1024, 1024, 952]);
+
+            for (i, pair) in windows.windows(2).enumerate() {
+                assert_eq!(pair[0][0], vec![i as i64 * 512]);
+                assert_eq!(pair[0][512..], pair[1][..512]);
+            }
+            assert_eq!(windows[4].last(), Some(&vec![2999]));
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_chunks_overlapping_reuses_window() -> Result <(), WaveReaderError> {
+            let samples: Vec <Vec <i64>> = (0..5000).map(|i| vec![i, -i]).collect();
+            let data_chunk = PCMWaveInfo::from_samples(samples.clone(), STEREO_16)?.data_chunks.remove(0);
+            let mut windows = data_chunk.chunks_overlapping(256, 64);
+
+            let mut start = 0;
+            let mut capacity = None;
+            while let Some(window) = windows.next() {
+                // Naive windows sliced from all of the samples
+                let end = (start + 256).min(samples.len());
+                assert_eq!(window, &samples[start..end]);
+                start += 64;
+
+                // The buffer keeps the capacity it had for the first window
+                let buffer_capacity = *capacity.get_or_insert(windows.window.capacity());
+                assert_eq!(windows.window.capacity(), buffer_capacity);
+                assert!(buffer_capacity >= 256 * 2);
+            }
+            assert_eq!(start, 64 * 76);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_histogram() -> Result <(), WaveReaderError> {
+            let mono_16 = PCMWaveFormatChunk { num_channels: 1, ..STEREO_16 };
+            let ramp: Vec <Vec <i64>> = (-32768..32768).map(|i| vec![i]).collect();
+            let data_chunk = PCMWaveInfo::from_samples(ramp, mono_16)?.data_chunks.remove(0);
+            assert_eq!(data_chunk.histogram(16), vec![4096; 16]);
+
+            let mono_8 = PCMWaveFormatChunk { num_channels: 1, bps: 8, ..STEREO_16 };
+            let ramp: Vec <Vec <i64>> = (-128..128).map(|i| vec![i]).collect();
+            let data_chunk = PCMWaveInfo::from_samples(ramp, mono_8)?.data_chunks.remove(0);
+            assert_eq!(data_chunk.histogram(3), vec![86, 85, 85]);
+
+            let data_chunk = PCMWaveInfo::from_samples(vec![vec![-32768, 32767]; 3], STEREO_16)?.data_chunks.remove(0);
+            assert_eq!(data_chunk.histogram(2), vec![3, 3]);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_frame_count() -> Result <(), WaveReaderError> {
+            let sine = crate::testutil::gen_sine(440.0, 44100, 0.25, 16, 2);
+            assert_eq!(sine.len(), 11025);
+            assert_eq!(PCMWaveInfo::from_samples(sine, STEREO_16)?.frame_count(), 3);
+            assert_eq!(PCMWaveInfo::from_samples(Vec::new(), STEREO_16)?.frame_count(), 0);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_dual_mono() -> Result <(), WaveReaderError> {
+            let sine = crate::testutil::gen_sine(440.0, 44100, 0.1, 16, 2);
+            assert!(PCMWaveInfo::from_samples(sine.clone(), STEREO_16)?.is_dual_mono());
+
+            let mut almost = sine.clone();
+            almost[2000][1] += 1;
+            assert!(!PCMWaveInfo::from_samples(almost, STEREO_16)?.is_dual_mono());
+
+            let mono_16 = PCMWaveFormatChunk { num_channels: 1, ..STEREO_16 };
+            let mono = sine.into_iter().map(|each_sample| vec![each_sample[0]]).collect();
+            assert!(!PCMWaveInfo::from_samples(mono, mono_16)?.is_dual_mono());
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_reversed() -> Result <(), WaveReaderError> {
+            let sine = crate::testutil::gen_sine(440.0, 44100, 0.01, 16, 2);
+            let forward: Vec <Vec <i64>> = PCMWaveInfo::from_samples(sine.clone(), STEREO_16)?.data_chunks.remove(0).collect();
+            let mut backward: Vec <Vec <i64>> = PCMWaveInfo::from_samples(sine, STEREO_16)?.data_chunks.remove(0).reversed().collect();
+
+            backward.reverse();
+            assert_eq!(backward, forward);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_reversed_truncated() -> Result <(), WaveReaderError> {
+            let content = [
+                0x64, 0x61, 0x74, 0x61, 0x0c, 0x0, 0x0, 0x0,
+                0x01, 0x0, 0x02, 0x0,
+                0x03, 0x0, 0x04, 0x0,
+                0x05, 0x0,
+            ];
+            let data_chunk = WaveReader::read_data_chunk(0, &STEREO_16, Cursor::new(&content), false)?;
+
+            assert_eq!(data_chunk.reversed().collect::<Vec <_>>(), vec![vec![3, 4], vec![1, 2]]);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_from_samples_bad_input() {
+            assert!(matches!(
+                PCMWaveInfo::from_samples(vec![vec![1, 2, 3]], STEREO_16),
+                Err(WaveReaderError::FormatMismatchError)
+            ));
+            assert!(matches!(
+                PCMWaveInfo::from_samples(vec![vec![1, 2]], PCMWaveFormatChunk { audio_format: 7, bps: 8, ..STEREO_16 }),
+                Err(WaveReaderError::UnsupportedFormat(7))
+            ));
+        }
+
+        #[test]
+        fn it_from_samples_out_of_range() {
+            let mono_20 = PCMWaveFormatChunk { num_channels: 1, bps: 20, ..STEREO_16 };
+
+            assert!(PCMWaveInfo::from_samples(vec![vec![-0x80000], vec![0x7FFFF]], mono_20).is_ok());
+            assert!(matches!(
+                PCMWaveInfo::from_samples(vec![vec![0], vec![0x80000]], mono_20),
+                Err(WaveReaderError::SampleRangeError)
+            ));
+            assert!(matches!(
+                PCMWaveInfo::from_samples(vec![vec![40000, 0]], STEREO_16),
+                Err(WaveReaderError::SampleRangeError)
+            ));
+            assert!(matches!(
+                PCMWaveInfo::from_samples(vec![vec![-129]], PCMWaveFormatChunk { num_channels: 1, bps: 8, ..STEREO_16 }),
+                Err(WaveReaderError::SampleRangeError)
+            ));
+        }
+
+        #[test]
+        fn it_bitrate_kbps() -> Result <(), WaveReaderError> {
+            // 1 second of 44100 Hz stereo 16-bit audio split over two data chunks
+            let first = open_data_chunk(&[&[0x64, 0x61, 0x74, 0x61, 0x10, 0x58, 0x01, 0x0][..], &[0x0; 88080][..]].concat(), &STEREO_16)?;
+            let second = open_data_chunk(&[&[0x64, 0x61, 0x74, 0x61, 0x00, 0x59, 0x01, 0x0][..], &[0x0; 88320][..]].concat(), &STEREO_16)?;
+            let wave_info = create_wave_info(STEREO_16, vec![first, second]);
+
+            assert!((wave_info.bitrate_kbps(125_000) - 1000.0).abs() < 1e-9);
+            assert!((wave_info.bitrate_kbps(176_400) - 1411.2).abs() < 1e-9);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_chains_data_chunks() -> Result <(), WaveReaderError> {
+            let first = open_data_chunk(
+                &[
+                    0x64, 0x61, 0x74, 0x61, 0x0c, 0x0, 0x0, 0x0,
+                    0x01, 0x0, 0x02, 0x0,
+                    0x03, 0x0, 0x04, 0x0,
+                    0x05, 0x0, 0x06, 0x0,
+                ],
+                &STEREO_16,
+            )?;
+            let second = open_data_chunk(
+                &[
+                    0x64, 0x61, 0x74, 0x61, 0x08, 0x0, 0x0, 0x0,
+                    0x07, 0x0, 0x08, 0x0,
+                    0x09, 0x0, 0x0a, 0x0,
+                ],
+                &STEREO_16,
+            )?;
+
+            let wave_info = create_wave_info(STEREO_16, vec![first, second]);
+            let samples: Vec <Vec <i64>> = wave_info.samples()?.collect();
+
+            assert_eq!(samples.len(), 3 + 2);
+            assert_eq!(samples[2], vec![5, 6]);
+            assert_eq!(samples[3], vec![7, 8]);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_channel_samples() -> Result <(), WaveReaderError> {
+            let content: &[u8] = &[
+                0x64, 0x61, 0x74, 0x61, 0x0c, 0x0, 0x0, 0x0,
+                0x01, 0x0, 0x02, 0x0,
+                0xff, 0xff, 0x04, 0x0,
+                0x05, 0x0, 0x06, 0x0,
+            ];
+            let data_chunk = open_data_chunk(content, &STEREO_16)?;
+            let left = create_wave_info(STEREO_16, vec![data_chunk]).channel_samples(0)?;
+
+            let data_chunk = open_data_chunk(content, &STEREO_16)?;
+            let right = create_wave_info(STEREO_16, vec![data_chunk]).channel_samples(1)?;
+
+            assert_eq!(left, vec![1, -1, 5]);
+            assert_eq!(right, vec![2, 4, 6]);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_bad_channel_index() -> Result <(), WaveReaderError> {
+            let data_chunk = open_data_chunk(
+                &[0x64, 0x61, 0x74, 0x61, 0x04, 0x0, 0x0, 0x0, 0x01, 0x0, 0x02, 0x0],
+                &STEREO_16,
+            )?;
+            let result = create_wave_info(STEREO_16, vec![data_chunk]).channel_samples(2);
+
+            assert!(matches!(result, Err(WaveReaderError::ChannelIndexError)));
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_bad_format() -> Result <(), WaveReaderError> {
+            let mono_16 = PCMWaveFormatChunk {
+                num_channels: 1,
+                ..STEREO_16
+            };
+            let first = open_data_chunk(
+                &[0x64, 0x61, 0x74, 0x61, 0x04, 0x0, 0x0, 0x0, 0x01, 0x0, 0x02, 0x0],
+                &STEREO_16,
+            )?;
+            let second = open_data_chunk(
+                &[0x64, 0x61, 0x74, 0x61, 0x02, 0x0, 0x0, 0x0, 0x03, 0x0],
+                &mono_16,
+            )?;
+
+            let wave_info = create_wave_info(STEREO_16, vec![first, second]);
+
+            assert!(wave_info.samples().is_err());
+
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod open_wav {
+        use super::*;
+        use std::io::Write;
+
+        fn create_temp_file(file_name: &str, content: &[u8]) -> Result <(), io::Error> {
+            let mut file = File::create(file_name)?;
+            file.write_all(content)?;
+
+            Ok(())
+        }
+
+        const CANONICAL: &[u8] = &[
+            0x52, 0x49, 0x46, 0x46, 0x28, 0x0, 0x0, 0x0, 0x57, 0x41, 0x56, 0x45,
+            0x66, 0x6d, 0x74, 0x20, 0x10, 0x0, 0x0, 0x0,
+            0x01, 0x0, 0x01, 0x0, 0x40, 0x1f, 0x0, 0x0, 0x40, 0x1f, 0x0, 0x0, 0x01, 0x0, 0x08, 0x0,
+            0x64, 0x61, 0x74, 0x61, 0x04, 0x0, 0x0, 0x0,
+            0x80, 0x81, 0x82, 0x83,
+        ];
+
+        const WITH_LIST: &[u8] = &[
+            0x52, 0x49, 0x46, 0x46, 0x34, 0x0, 0x0, 0x0, 0x57, 0x41, 0x56, 0x45,
+            0x66, 0x6d, 0x74, 0x20, 0x10, 0x0, 0x0, 0x0,
+            0x01, 0x0, 0x01, 0x0, 0x40, 0x1f, 0x0, 0x0, 0x40, 0x1f, 0x0, 0x0, 0x01, 0x0, 0x08, 0x0,
+            0x4c, 0x49, 0x53, 0x54, 0x04, 0x0, 0x0, 0x0, 0x49, 0x4e, 0x46, 0x4f,
+            0x64, 0x61, 0x74, 0x61, 0x04, 0x0, 0x0, 0x0,
+            0x80, 0x81, 0x82, 0x83,
+        ];
+
+        fn build_wav(is_big_endian: bool, bps: u16, samples: &[i64]) -> Vec <u8> {
+            let to_bytes = |value: u32, len: usize| -> Vec <u8> {
+                if is_big_endian {
+                    value.to_be_bytes()[4 - len..].to_vec()
+                } else {
+                    value.to_le_bytes()[..len].to_vec()
+                }
+            };
+            let bytes_per_sample = bps as usize / 8;
+            let data: Vec <u8> = samples.iter().flat_map(|sample| to_bytes(*sample as u32, bytes_per_sample)).collect();
+
+            [
+                &if is_big_endian { *b"RIFX" } else { *b"RIFF" }[..], &to_bytes(36 + data.len() as u32, 4), b"WAVE",
+                b"fmt ", &to_bytes(16, 4),
+                &to_bytes(1, 2), &to_bytes(1, 2), &to_bytes(44100, 4),
+                &to_bytes(44100 * bytes_per_sample as u32, 4), &to_bytes(bytes_per_sample as u32, 2), &to_bytes(u32::from(bps), 2),
+                b"data", &to_bytes(data.len() as u32, 4), &data,
+            ].concat()
+        }
+
+        #[test]
+        fn it_rifx_samples() -> Result <(), WaveReaderError> {
+            let cases: [(u16, Vec <i64>); 3] = [
+                (16, vec![1, -2, 0x1234, -32768]),
+                (24, vec![1, -2, 0x123456, -8_388_608]),
+                (32, vec![1, -2, 0x12345678, i64::from(i32::MIN)]),
+            ];
+
+            for (bps, expected) in cases {
+                let big_endian = WaveReader::read_pcm(Cursor::new(build_wav(true, bps, &expected)))?;
+                let little_endian = WaveReader::read_pcm(Cursor::new(build_wav(false, bps, &expected)))?;
+
+                assert!(big_endian.riff_header.is_big_endian);
+                assert!(big_endian.data_chunks[0].is_big_endian);
+                assert_eq!(big_endian.fmt_header.samp_rate, 44100);
+                assert_eq!(big_endian.fmt_header.bps, bps);
+                assert_eq!(big_endian.channel_samples(0)?, expected);
+                assert_eq!(little_endian.channel_samples(0)?, expected);
+            }
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_canonical() -> Result <(), WaveReaderError> {
+            let file_name = "midp_it_canonical.wav.part";
+            create_temp_file(file_name, CANONICAL)?;
+            let strict = WaveReader::open_pcm_strict(file_name);
+            let lenient = WaveReader::open_pcm(file_name);
+            std::fs::remove_file(file_name)?;
+
+            assert_eq!(strict?.data_chunks[0].size_bytes, 4);
+            assert_eq!(lenient?.data_chunks[0].data_start, 44);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_list_chunk() -> Result <(), WaveReaderError> {
+            let file_name = "midp_it_list_chunk.wav.part";
+            create_temp_file(file_name, WITH_LIST)?;
+            let strict = WaveReader::open_pcm_strict(file_name);
+            let lenient = WaveReader::open_pcm(file_name);
+            std::fs::remove_file(file_name)?;
+
+            assert!(matches!(strict, Err(WaveReaderError::NonCanonical)));
+            assert_eq!(lenient?.data_chunks[0].data_start, 56);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_odd_block_align() -> Result <(), WaveReaderError> {
+            // 3 channels of 8-bit samples, with a pad byte after the odd-sized
+            // data chunk and a cue chunk after that
+            let content: &[u8] = &[
+                0x52, 0x49, 0x46, 0x46, 0x52, 0x0, 0x0, 0x0, 0x57, 0x41, 0x56, 0x45,
+                0x66, 0x6d, 0x74, 0x20, 0x10, 0x0, 0x0, 0x0,
+                0x01, 0x0, 0x03, 0x0, 0x40, 0x1f, 0x0, 0x0, 0xc0, 0x5d, 0x0, 0x0, 0x03, 0x0, 0x08, 0x0,
+                0x64, 0x61, 0x74, 0x61, 0x09, 0x0, 0x0, 0x0,
+                0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09,
+                0x0,
+                0x63, 0x75, 0x65, 0x20, 0x1c, 0x0, 0x0, 0x0, 0x01, 0x0, 0x0, 0x0,
+                0x07, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x64, 0x61, 0x74, 0x61,
+                0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x02, 0x0, 0x0, 0x0,
+            ];
+            let file_name = "midp_it_odd_block_align.wav.part";
+            create_temp_file(file_name, content)?;
+            let result = WaveReader::open_pcm(file_name);
+            std::fs::remove_file(file_name)?;
+
+            let wave_info = result?;
+            assert_eq!(wave_info.cue_points, vec![CuePoint { id: 7, sample_offset: 2 }]);
+            assert!(wave_info.file_size_matches(content.len() as u64));
+
+            let samples: Vec <Vec <i64>> = wave_info.samples()?.collect();
+            assert_eq!(samples, vec![vec![-127, -126, -125], vec![-124, -123, -122], vec![-121, -120, -119]]);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_skips_chunks_before_data() -> Result <(), WaveReaderError> {
+            // fact, then bext with an odd size and its padding byte, then LIST
+            let extra_chunks: &[u8] = &[
+                0x66, 0x61, 0x63, 0x74, 0x04, 0x0, 0x0, 0x0, 0x04, 0x0, 0x0, 0x0,
+                0x62, 0x65, 0x78, 0x74, 0x03, 0x0, 0x0, 0x0, 0x61, 0x62, 0x63, 0x0,
+                0x4c, 0x49, 0x53, 0x54, 0x04, 0x0, 0x0, 0x0, 0x49, 0x4e, 0x46, 0x4f,
+            ];
+            let content = [&CANONICAL[..36], extra_chunks, &CANONICAL[36..]].concat();
+
+            let wave_info = WaveReader::read_pcm(Cursor::new(&content))?;
+            assert_eq!(wave_info.data_chunks[0].data_start, 44 + extra_chunks.len() as u64);
+            assert_eq!(wave_info.channel_samples(0)?, vec![0, 1, 2, 3]);
+
+            let no_data = [&CANONICAL[..36], extra_chunks].concat();
+            assert!(matches!(WaveReader::read_pcm(Cursor::new(&no_data)), Err(WaveReaderError::UnexpectedEof)));
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_two_data_chunks() -> Result <(), WaveReaderError> {
+            let file_name = "midp_it_two_data_chunks.wav.part";
+            let second_data: &[u8] = &[
+                0x64, 0x61, 0x74, 0x61, 0x03, 0x0, 0x0, 0x0,
+                0x90, 0x91, 0x92,
+                0x0,
+            ];
+            let content = [CANONICAL, second_data].concat();
+            create_temp_file(file_name, &content)?;
+            let result = WaveReader::open_pcm(file_name);
+            let strict = WaveReader::open_pcm_strict(file_name);
+            std::fs::remove_file(file_name)?;
+
+            assert_eq!(strict?.data_chunks.len(), 2);
+            let wave_info = result?;
+            let data_starts: Vec <u64> = wave_info.data_chunks.iter().map(|chunk| chunk.data_start).collect();
+            assert_eq!(data_starts, vec![44, 56]);
+            assert_eq!(wave_info.channel_samples(0)?, vec![0, 1, 2, 3, 16, 17, 18]);
+
+            assert!(WaveReader::is_valid_wav(&content));
+            let from_memory = WaveReader::read_pcm(Cursor::new(&content))?;
+            assert_eq!(from_memory.channel_samples(0)?, vec![0, 1, 2, 3, 16, 17, 18]);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_non_clone_reader() -> Result <(), WaveReaderError> {
+            let second_data: &[u8] = &[
+                0x64, 0x61, 0x74, 0x61, 0x03, 0x0, 0x0, 0x0,
+                0x90, 0x91, 0x92,
+                0x0,
+            ];
+            let content = [CANONICAL, second_data].concat();
+            let mut cursor = Cursor::new(&content);
+
+            // `&mut Cursor` is not `Clone`, so both data chunks share it
+            let wave_info = WaveReader::read_pcm(&mut cursor)?;
+            let mut data_chunks = wave_info.data_chunks.into_iter();
+            let mut first = data_chunks.next().unwrap();
+            let second = data_chunks.next().unwrap();
+            assert_eq!(first.next(), Some(vec![0]));
+            assert_eq!(second.flatten().collect::<Vec <i64>>(), vec![16, 17, 18]);
+            assert_eq!(first.flatten().collect::<Vec <i64>>(), vec![1, 2, 3]);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_path_buf() -> Result <(), WaveReaderError> {
+            let file_path = std::path::PathBuf::from("midp_it_path_buf.wav.part");
+            create_temp_file(file_path.to_str().unwrap(), CANONICAL)?;
+            let result = WaveReader::open_pcm(&file_path);
+            std::fs::remove_file(&file_path)?;
+
+            assert_eq!(result?.fmt_header.samp_rate, 8000);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_cue_chunk() -> Result <(), WaveReaderError> {
+            let file_name = "midp_it_cue_chunk.wav.part";
+            let cue_chunk: &[u8] = &[
+                0x63, 0x75, 0x65, 0x20, 0x34, 0x0, 0x0, 0x0,
+                0x02, 0x0, 0x0, 0x0,
+                0x01, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x64, 0x61, 0x74, 0x61,
+                0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x01, 0x0, 0x0, 0x0,
+                0x02, 0x0, 0x0, 0x0, 0x03, 0x0, 0x0, 0x0, 0x64, 0x61, 0x74, 0x61,
+                0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x03, 0x0, 0x0, 0x0,
+            ];
+            create_temp_file(file_name, &[CANONICAL, cue_chunk].concat())?;
+            let result = WaveReader::open_pcm(file_name);
+            std::fs::remove_file(file_name)?;
+
+            let wave_info = result?;
+            assert_eq!(wave_info.cue_points, vec![
+                CuePoint { id: 1, sample_offset: 1 },
+                CuePoint { id: 2, sample_offset: 3 },
+            ]);
+            assert_eq!(wave_info.data_chunks[0].data_start, 44);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_inst_chunk() -> Result <(), WaveReaderError> {
+            let file_name = "midp_it_inst_chunk.wav.part";
+            let inst_chunk: &[u8] = &[
+                0x69, 0x6e, 0x73, 0x74, 0x07, 0x0, 0x0, 0x0,
+                0x3c, 0xfb, 0xfa, 0x30, 0x48, 0x01, 0x7f,
+                0x0,
+            ];
+            create_temp_file(file_name, &[CANONICAL, inst_chunk].concat())?;
+            let result = WaveReader::open_pcm(file_name);
+            std::fs::remove_file(file_name)?;
+
+            let instrument = result?.instrument.unwrap();
+            assert_eq!(instrument.unshifted_note, 60);
+            assert_eq!(instrument.fine_tune, -5);
+            assert_eq!(instrument.gain, -6);
+            assert_eq!((instrument.low_note, instrument.high_note), (48, 72));
+            assert_eq!((instrument.low_velocity, instrument.high_velocity), (1, 127));
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_ixml_chunk() -> Result <(), WaveReaderError> {
+            let file_name = "midp_it_ixml_chunk.wav.part";
+            let xml = b"<BWFXML><SCENE>12A</SCENE></BWFXML>";
+            let ixml_chunk = [b"iXML", &(xml.len() as u32 + 1).to_le_bytes()[..], xml, &[0x0]].concat();
+            let axml_chunk = [b"axml", &[0x02, 0x0, 0x0, 0x0][..], &[0xc3, 0x28]].concat();
+            create_temp_file(file_name, &[CANONICAL, &ixml_chunk, &axml_chunk].concat())?;
+            let result = WaveReader::open_pcm(file_name);
+            std::fs::remove_file(file_name)?;
+
+            let wave_info = result?;
+            assert_eq!(wave_info.ixml, Some(XmlMetadata::Text(String::from("<BWFXML><SCENE>12A</SCENE></BWFXML>"))));
+            assert_eq!(wave_info.axml, Some(XmlMetadata::Bytes(vec![0xc3, 0x28])));
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_trailing_chunk() -> Result <(), WaveReaderError> {
+            let file_name = "midp_it_trailing_chunk.wav.part";
+            let id3_chunk: &[u8] = &[
+                0x69, 0x64, 0x33, 0x20, 0x03, 0x0, 0x0, 0x0,
+                0x49, 0x44, 0x33,
+                0x0,
+            ];
+            create_temp_file(file_name, &[WITH_LIST, id3_chunk].concat())?;
+            let result = WaveReader::open_pcm(file_name);
+            std::fs::remove_file(file_name)?;
+
+            let wave_info = result?;
+            assert_eq!(wave_info.metadata_chunks.keys().collect::<Vec <_>>(), vec![b"LIST", b"id3 "]);
+            assert_eq!(wave_info.metadata_chunks[b"id3 "], vec![b"ID3"]);
+            assert_eq!(wave_info.metadata_chunks[b"LIST"], vec![b"INFO"]);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_list_chunks_around_data() -> Result <(), WaveReaderError> {
+            let adtl_chunk: &[u8] = &[
+                0x4c, 0x49, 0x53, 0x54, 0x04, 0x0, 0x0, 0x0,
+                0x61, 0x64, 0x74, 0x6c,
+            ];
+            let wave_info = WaveReader::read_pcm(Cursor::new([WITH_LIST, adtl_chunk].concat()))?;
+
+            assert_eq!(wave_info.metadata_chunks.keys().collect::<Vec <_>>(), vec![b"LIST"]);
+            assert_eq!(wave_info.metadata_chunks[b"LIST"], vec![b"INFO", b"adtl"]);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_id3_tags() -> Result <(), WaveReaderError> {
+            let file_name = "midp_it_id3_tags.wav.part";
+            let frames = [
+                &b"TIT2\x00\x00\x00\x0a\x00\x00\x00Episode 1"[..],
+                &b"TPE1\x00\x00\x00\x05\x00\x00\x01\xff\xfeM\x00"[..],
+                &b"TXXX\x00\x00\x00\x03\x00\x00\x03a\x00"[..],
+            ].concat();
+            // 200 bytes of tag content gives the syncsafe size 0x01 0x48
+            let padding = vec![0x0; 200 - frames.len()];
+            let tag = [&b"ID3\x03\x00\x00\x00\x00\x01\x48"[..], &frames, &padding].concat();
+            let id3_chunk = [b"id3 ", &(tag.len() as u32).to_le_bytes()[..], &tag].concat();
+            create_temp_file(file_name, &[CANONICAL, &id3_chunk].concat())?;
+            let result = WaveReader::open_pcm(file_name);
+            std::fs::remove_file(file_name)?;
+
+            let tags = result?.tags;
+            assert_eq!(tags.get("TIT2").map(String::as_str), Some("Episode 1"));
+            assert_eq!(tags.get("TPE1").map(String::as_str), Some("M"));
+            assert_eq!(tags.len(), 2);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_id3_tags_v4_syncsafe_frames() {
+            let tag = [
+                &b"ID3\x04\x00\x00\x00\x00\x01\x0b"[..],
+                &b"TALB\x00\x00\x01\x01\x00\x00\x03"[..],
+                &[b'a'; 128],
+            ].concat();
+
+            assert_eq!(parse_id3_tags(&tag).get("TALB"), Some(&"a".repeat(128)));
+            assert!(parse_id3_tags(b"ID3\x02\x00\x00\x00\x00\x00\x00").is_empty());
+        }
+
+        #[test]
+        fn it_no_cue_chunk() -> Result <(), WaveReaderError> {
+            let file_name = "midp_it_no_cue_chunk.wav.part";
+            create_temp_file(file_name, CANONICAL)?;
+            let result = WaveReader::open_pcm(file_name);
+            std::fs::remove_file(file_name)?;
+
+            let wave_info = result?;
+            assert!(wave_info.cue_points.is_empty());
+            assert!(wave_info.instrument.is_none());
+            assert!(wave_info.ixml.is_none());
+            assert!(wave_info.metadata_chunks.is_empty());
+            assert!(wave_info.tags.is_empty());
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_malformed_optional_chunks() -> Result <(), WaveReaderError> {
+            // The cue chunk declares 9 cue points but only has room for 1
+            let cue_chunk: &[u8] = &[
+                0x63, 0x75, 0x65, 0x20, 0x1c, 0x0, 0x0, 0x0,
+                0x09, 0x0, 0x0, 0x0,
+                0x01, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x64, 0x61, 0x74, 0x61,
+                0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x01, 0x0, 0x0, 0x0,
+            ];
+            // The inst chunk is too short for its parameters
+            let inst_chunk: &[u8] = &[0x69, 0x6e, 0x73, 0x74, 0x02, 0x0, 0x0, 0x0, 0x3c, 0xfb];
+            // The trailing chunk is cut short by the end of the file
+            let truncated_chunk: &[u8] = &[0x69, 0x64, 0x33, 0x20, 0x10, 0x0, 0x0, 0x0, 0x49, 0x44, 0x33];
+            let content = [CANONICAL, cue_chunk, inst_chunk, truncated_chunk].concat();
+
+            let wave_info = WaveReader::read_pcm(Cursor::new(&content))?;
+            assert!(wave_info.cue_points.is_empty());
+            assert!(wave_info.instrument.is_none());
+            assert!(wave_info.metadata_chunks.is_empty());
+            assert_eq!(wave_info.channel_samples(0)?, vec![0, 1, 2, 3]);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_valid_bytes() {
+            assert!(WaveReader::is_valid_wav(CANONICAL));
+            assert!(WaveReader::is_valid_wav(WITH_LIST));
+        }
+
+        #[test]
+        fn it_truncated_bytes() {
+            assert!(!WaveReader::is_valid_wav(&CANONICAL[..CANONICAL.len() - 2]));
+            assert!(!WaveReader::is_valid_wav(&CANONICAL[..20]));
+            assert!(!WaveReader::is_valid_wav(&[]));
+        }
+
+        #[test]
+        fn it_duplicate_fmt() -> Result <(), WaveReaderError> {
+            let file_name = "midp_it_duplicate_fmt.wav.part";
+            let fmt_chunk: &[u8] = &[
+                0x66, 0x6d, 0x74, 0x20, 0x10, 0x0, 0x0, 0x0,
+                0x01, 0x0, 0x02, 0x0, 0x44, 0xac, 0x0, 0x0, 0x88, 0x58, 0x01, 0x0, 0x02, 0x0, 0x08, 0x0,
+            ];
+            create_temp_file(file_name, &[CANONICAL, fmt_chunk].concat())?;
+            let strict = WaveReader::open_pcm_strict(file_name);
+            let lenient = WaveReader::open_pcm(file_name);
+            std::fs::remove_file(file_name)?;
+
+            assert!(matches!(strict, Err(WaveReaderError::DuplicateChunkError)));
+            assert!(matches!(lenient, Err(WaveReaderError::DuplicateChunkError)));
+
+            Ok(())
+        }
+
+        #[test]
+        #[cfg(feature = "mmap")]
+        fn it_mmap() -> Result <(), WaveReaderError> {
+            let file_name = "midp_it_mmap.wav.part";
+            create_temp_file(file_name, WITH_LIST)?;
+            let buffered = WaveReader::open_pcm(file_name);
+            let mapped = WaveReader::open_pcm_mmap(file_name);
+
+            let buffered_samples: Vec <Vec <i64>> = buffered?.samples()?.collect();
+            let mapped_samples: Vec <Vec <i64>> = mapped?.samples()?.collect();
+            std::fs::remove_file(file_name)?;
+
+            assert_eq!(mapped_samples.len(), 4);
+            assert_eq!(mapped_samples, buffered_samples);
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_file_size_matches() -> Result <(), WaveReaderError> {
+            let wave_info = WaveReader::read_pcm(Cursor::new(CANONICAL))?;
+
+            assert!(wave_info.file_size_matches(CANONICAL.len() as u64));
+            assert!(!wave_info.file_size_matches(CANONICAL.len() as u64 + 1));
+            assert!(!wave_info.file_size_matches(0));
+
+            Ok(())
+        }
+
+        #[test]
+        fn it_wrong_file_size() -> Result <(), WaveReaderError> {
+            // WITH_LIST declares the file size of CANONICAL
+            let mut content = WITH_LIST.to_vec();
+            content[4] = CANONICAL[4];
+            let wave_info = WaveReader::read_pcm(Cursor::new(&content))?;
+
+            assert!(!wave_info.file_size_matches(content.len() as u64));
+
+            Ok(())
+        }
     }
 }
\ No newline at end of file